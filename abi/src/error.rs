@@ -0,0 +1,309 @@
+//! Errors returned by operations and queries.
+//!
+//! Clients should match on the variant, or on its `code()` when they only see
+//! the GraphQL error; the `Display` text is a default English message.
+
+use std::fmt;
+
+use async_graphql::{Error, ErrorExtensions};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rules::{self, RuleError},
+    RoomStatus,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameError {
+    // lookups
+    NotAuthenticated,
+    RoomNotFound { room_id: u32 },
+    TournamentNotFound { tournament_id: u64 },
+    BracketNotFound { tournament_id: u64 },
+    MatchNotFound { match_id: u64 },
+    GuildNotFound { guild_id: u64 },
+    ReplayNotFound { replay_id: u64 },
+    PlayerNotFound,
+
+    // input validation
+    InvalidLength { field: String, min: u32, max: u32 },
+    InvalidTimeBank,
+    InvalidIncrement,
+    IncrementRequiresSpeed,
+    InvalidSeriesLength { length: u32 },
+    InvalidPlayerLimit { min: u32, max: u32 },
+    InvalidPrizeDistribution { total: u32 },
+    TooManyPrizePlaces { max: u32 },
+    CellOutOfRange,
+    InvalidBoard,
+    InvalidSideToMove,
+
+    // rooms
+    InvalidRoomStatus { status: RoomStatus },
+    InvalidPassword,
+    CannotJoinOwnRoom,
+    NotInRoom,
+    NotRoomCreator,
+    NotYourTurn,
+    NotUltimate,
+    UltimateMoveRequired,
+    CannotChallengeSelf,
+
+    // game rules
+    GameOver,
+    PositionOutOfRange,
+    PositionTaken,
+    WrongSubBoard { board: u32 },
+    SubBoardClosed,
+    PowerUpsDisabled,
+    PowerUpNotImplemented,
+    PowerUpNotOwned,
+    TimeExpired,
+    OwnTurn,
+    OpponentHasTime,
+    AnalysisUnavailable,
+
+    // offers and takebacks
+    OfferPending { kind: OfferKind },
+    NoOffer { kind: OfferKind },
+    OwnOffer { kind: OfferKind },
+    NothingToTakeBack,
+    NotLastMover,
+    TakebacksUnavailable,
+
+    // replays
+    NoFinishedGame,
+    NotAPlayer,
+    ReplayUnavailable,
+
+    // economy
+    InsufficientBalance { required: u64, available: u64 },
+    InsufficientFunds,
+    InvalidAmount,
+    NoRewards,
+
+    // tournaments
+    RegistrationClosed,
+    AlreadyRegistered,
+    NotTournamentCreator,
+    NotEnoughPlayers { min: u32 },
+    NotMatchParticipant,
+
+    // guilds
+    AlreadyInGuild,
+    PlayerAlreadyInGuild,
+    NotInGuild,
+    InsufficientPermissions,
+    GuildTagTaken,
+
+    // upgrades
+    MigrationInProgress,
+}
+
+/// What a pending offer is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OfferKind {
+    Rematch,
+    Draw,
+    Takeback,
+}
+
+impl fmt::Display for OfferKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            OfferKind::Rematch => "rematch",
+            OfferKind::Draw => "draw",
+            OfferKind::Takeback => "takeback",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl GameError {
+    pub fn invalid_length(field: &str, min: u32, max: u32) -> Self {
+        GameError::InvalidLength {
+            field: field.to_string(),
+            min,
+            max,
+        }
+    }
+
+    /// Stable identifier of the error, used as the GraphQL error code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::NotAuthenticated => "NOT_AUTHENTICATED",
+            GameError::RoomNotFound { .. } => "ROOM_NOT_FOUND",
+            GameError::TournamentNotFound { .. } => "TOURNAMENT_NOT_FOUND",
+            GameError::BracketNotFound { .. } => "BRACKET_NOT_FOUND",
+            GameError::MatchNotFound { .. } => "MATCH_NOT_FOUND",
+            GameError::GuildNotFound { .. } => "GUILD_NOT_FOUND",
+            GameError::ReplayNotFound { .. } => "REPLAY_NOT_FOUND",
+            GameError::PlayerNotFound => "PLAYER_NOT_FOUND",
+            GameError::InvalidLength { .. } => "INVALID_LENGTH",
+            GameError::InvalidTimeBank => "INVALID_TIME_BANK",
+            GameError::InvalidIncrement => "INVALID_INCREMENT",
+            GameError::IncrementRequiresSpeed => "INCREMENT_REQUIRES_SPEED",
+            GameError::InvalidSeriesLength { .. } => "INVALID_SERIES_LENGTH",
+            GameError::InvalidPlayerLimit { .. } => "INVALID_PLAYER_LIMIT",
+            GameError::InvalidPrizeDistribution { .. } => "INVALID_PRIZE_DISTRIBUTION",
+            GameError::TooManyPrizePlaces { .. } => "TOO_MANY_PRIZE_PLACES",
+            GameError::CellOutOfRange => "CELL_OUT_OF_RANGE",
+            GameError::InvalidBoard => "INVALID_BOARD",
+            GameError::InvalidSideToMove => "INVALID_SIDE_TO_MOVE",
+            GameError::InvalidRoomStatus { .. } => "INVALID_ROOM_STATUS",
+            GameError::InvalidPassword => "INVALID_PASSWORD",
+            GameError::CannotJoinOwnRoom => "CANNOT_JOIN_OWN_ROOM",
+            GameError::NotInRoom => "NOT_IN_ROOM",
+            GameError::NotRoomCreator => "NOT_ROOM_CREATOR",
+            GameError::NotYourTurn => "NOT_YOUR_TURN",
+            GameError::NotUltimate => "NOT_ULTIMATE",
+            GameError::UltimateMoveRequired => "ULTIMATE_MOVE_REQUIRED",
+            GameError::CannotChallengeSelf => "CANNOT_CHALLENGE_SELF",
+            GameError::GameOver => "GAME_OVER",
+            GameError::PositionOutOfRange => "POSITION_OUT_OF_RANGE",
+            GameError::PositionTaken => "POSITION_TAKEN",
+            GameError::WrongSubBoard { .. } => "WRONG_SUB_BOARD",
+            GameError::SubBoardClosed => "SUB_BOARD_CLOSED",
+            GameError::PowerUpsDisabled => "POWER_UPS_DISABLED",
+            GameError::PowerUpNotImplemented => "POWER_UP_NOT_IMPLEMENTED",
+            GameError::PowerUpNotOwned => "POWER_UP_NOT_OWNED",
+            GameError::TimeExpired => "TIME_EXPIRED",
+            GameError::OwnTurn => "OWN_TURN",
+            GameError::OpponentHasTime => "OPPONENT_HAS_TIME",
+            GameError::AnalysisUnavailable => "ANALYSIS_UNAVAILABLE",
+            GameError::OfferPending { .. } => "OFFER_PENDING",
+            GameError::NoOffer { .. } => "NO_OFFER",
+            GameError::OwnOffer { .. } => "OWN_OFFER",
+            GameError::NothingToTakeBack => "NOTHING_TO_TAKE_BACK",
+            GameError::NotLastMover => "NOT_LAST_MOVER",
+            GameError::TakebacksUnavailable => "TAKEBACKS_UNAVAILABLE",
+            GameError::NoFinishedGame => "NO_FINISHED_GAME",
+            GameError::NotAPlayer => "NOT_A_PLAYER",
+            GameError::ReplayUnavailable => "REPLAY_UNAVAILABLE",
+            GameError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+            GameError::InsufficientFunds => "INSUFFICIENT_FUNDS",
+            GameError::InvalidAmount => "INVALID_AMOUNT",
+            GameError::NoRewards => "NO_REWARDS",
+            GameError::RegistrationClosed => "REGISTRATION_CLOSED",
+            GameError::AlreadyRegistered => "ALREADY_REGISTERED",
+            GameError::NotTournamentCreator => "NOT_TOURNAMENT_CREATOR",
+            GameError::NotEnoughPlayers { .. } => "NOT_ENOUGH_PLAYERS",
+            GameError::NotMatchParticipant => "NOT_MATCH_PARTICIPANT",
+            GameError::AlreadyInGuild => "ALREADY_IN_GUILD",
+            GameError::PlayerAlreadyInGuild => "PLAYER_ALREADY_IN_GUILD",
+            GameError::NotInGuild => "NOT_IN_GUILD",
+            GameError::InsufficientPermissions => "INSUFFICIENT_PERMISSIONS",
+            GameError::GuildTagTaken => "GUILD_TAG_TAKEN",
+            GameError::MigrationInProgress => "MIGRATION_IN_PROGRESS",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NotAuthenticated => write!(f, "Action must be authenticated"),
+            GameError::RoomNotFound { room_id } => write!(f, "Room {} not found", room_id),
+            GameError::TournamentNotFound { tournament_id } => write!(f, "Tournament {} not found", tournament_id),
+            GameError::BracketNotFound { tournament_id } => {
+                write!(f, "Tournament {} has no bracket yet", tournament_id)
+            }
+            GameError::MatchNotFound { match_id } => write!(f, "Match {} not found", match_id),
+            GameError::GuildNotFound { guild_id } => write!(f, "Guild {} not found", guild_id),
+            GameError::ReplayNotFound { replay_id } => write!(f, "Replay {} not found", replay_id),
+            GameError::PlayerNotFound => write!(f, "Player not found"),
+            GameError::InvalidLength { field, min, max } => {
+                write!(f, "{} must be {}-{} characters", field, min, max)
+            }
+            GameError::InvalidTimeBank => {
+                write!(f, "Speed time bank must be 1-{} seconds", rules::MAX_TIME_BANK_SECS)
+            }
+            GameError::InvalidIncrement => {
+                write!(f, "Increment can be at most {} seconds", rules::MAX_INCREMENT_SECS)
+            }
+            GameError::IncrementRequiresSpeed => write!(f, "Increment is only available in Speed mode"),
+            GameError::InvalidSeriesLength { length } => {
+                write!(f, "Series length must be an odd number from 1 to 15, got {}", length)
+            }
+            GameError::InvalidPlayerLimit { min, max } => write!(f, "Tournament must have {}-{} players", min, max),
+            GameError::InvalidPrizeDistribution { total } => {
+                write!(f, "Prize distribution must sum to 100%, got {}%", total)
+            }
+            GameError::TooManyPrizePlaces { max } => write!(f, "Prizes can be paid to at most {} places", max),
+            GameError::CellOutOfRange => write!(f, "Board and cell must be 0-8"),
+            GameError::InvalidBoard => write!(f, "Cells must be X, O or empty"),
+            GameError::InvalidSideToMove => write!(f, "Side to move must be X or O"),
+            GameError::InvalidRoomStatus { status } => write!(f, "Room is {}", status),
+            GameError::InvalidPassword => write!(f, "Invalid password"),
+            GameError::CannotJoinOwnRoom => write!(f, "Cannot join your own game"),
+            GameError::NotInRoom => write!(f, "You're not in this room"),
+            GameError::NotRoomCreator => write!(f, "Only the room's creator can cancel it"),
+            GameError::NotYourTurn => write!(f, "Not your turn"),
+            GameError::NotUltimate => write!(f, "Not an Ultimate game"),
+            GameError::UltimateMoveRequired => write!(f, "Use MakeUltimateMove in Ultimate rooms"),
+            GameError::CannotChallengeSelf => write!(f, "Cannot challenge yourself"),
+            GameError::GameOver => write!(f, "Game has finished"),
+            GameError::PositionOutOfRange => write!(f, "Position out of range"),
+            GameError::PositionTaken => write!(f, "Position already taken"),
+            GameError::WrongSubBoard { board } => write!(f, "You must play in sub-board {}", board),
+            GameError::SubBoardClosed => write!(f, "Sub-board is already closed"),
+            GameError::PowerUpsDisabled => write!(f, "Power-ups not enabled in this game mode"),
+            GameError::PowerUpNotImplemented => write!(f, "Power-up not yet implemented"),
+            GameError::PowerUpNotOwned => write!(f, "You don't have this power-up"),
+            GameError::TimeExpired => write!(f, "Time limit exceeded"),
+            GameError::OwnTurn => write!(f, "Cannot claim a timeout on your own turn"),
+            GameError::OpponentHasTime => write!(f, "Opponent has not timed out yet"),
+            GameError::AnalysisUnavailable => write!(f, "Analysis is only available on 3x3 boards"),
+            GameError::OfferPending { kind } => write!(f, "A {} offer is already pending", kind),
+            GameError::NoOffer { kind } => write!(f, "No {} offer to accept", kind),
+            GameError::OwnOffer { kind } => write!(f, "Cannot accept your own {} offer", kind),
+            GameError::NothingToTakeBack => write!(f, "No moves to take back"),
+            GameError::NotLastMover => write!(f, "You can only take back your own last move"),
+            GameError::TakebacksUnavailable => write!(f, "Takebacks are not available in PowerUp mode"),
+            GameError::NoFinishedGame => write!(f, "No finished game to save in this room"),
+            GameError::NotAPlayer => write!(f, "You did not play in this game"),
+            GameError::ReplayUnavailable => write!(f, "Replay is no longer available"),
+            GameError::InsufficientBalance { required, available } => {
+                write!(f, "Insufficient balance: {} required, {} available", required, available)
+            }
+            GameError::InsufficientFunds => write!(f, "Your account on this chain doesn't hold enough tokens"),
+            GameError::InvalidAmount => write!(f, "Amount must be at least 1 token"),
+            GameError::NoRewards => write!(f, "No rewards to claim"),
+            GameError::RegistrationClosed => write!(f, "Tournament not accepting registrations"),
+            GameError::AlreadyRegistered => write!(f, "Already registered"),
+            GameError::NotTournamentCreator => write!(f, "Only tournament creator can start"),
+            GameError::NotEnoughPlayers { min } => write!(f, "Need at least {} players to start", min),
+            GameError::NotMatchParticipant => write!(f, "Not authorized to report this match"),
+            GameError::AlreadyInGuild => write!(f, "Already in a guild"),
+            GameError::PlayerAlreadyInGuild => write!(f, "Player is already in a guild"),
+            GameError::NotInGuild => write!(f, "You're not in this guild"),
+            GameError::InsufficientPermissions => write!(f, "Insufficient permissions to invite"),
+            GameError::GuildTagTaken => write!(f, "Guild tag already taken"),
+            GameError::MigrationInProgress => {
+                write!(f, "Stored data is being upgraded; send the operation again")
+            }
+        }
+    }
+}
+
+impl From<RuleError> for GameError {
+    fn from(error: RuleError) -> Self {
+        match error {
+            RuleError::GameOver => GameError::GameOver,
+            RuleError::PositionOutOfRange => GameError::PositionOutOfRange,
+            RuleError::PositionTaken => GameError::PositionTaken,
+            RuleError::WrongSubBoard(board) => GameError::WrongSubBoard { board },
+            RuleError::SubBoardClosed => GameError::SubBoardClosed,
+            RuleError::PowerUpsDisabled => GameError::PowerUpsDisabled,
+            RuleError::PowerUpNotImplemented => GameError::PowerUpNotImplemented,
+            RuleError::TimeExpired => GameError::TimeExpired,
+        }
+    }
+}
+
+/// Exposes the error to GraphQL clients with its code in `extensions.code`.
+impl ErrorExtensions for GameError {
+    fn extend(&self) -> Error {
+        Error::new(self.to_string()).extend_with(|_, extensions| extensions.set("code", self.code()))
+    }
+}
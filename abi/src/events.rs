@@ -0,0 +1,65 @@
+//! Events emitted by the contract, so clients can follow a room or the lobby
+//! as blocks are added instead of polling the service.
+//!
+//! Every room has its own stream, named by `room_stream`. The lobby stream
+//! carries what changes the room and tournament lists. The service keeps a
+//! copy of both, numbered like the streams, for clients that can't read
+//! events from blocks.
+
+use async_graphql::scalar;
+use linera_sdk::linera_base_types::{AccountOwner, StreamName};
+use serde::{Deserialize, Serialize};
+
+use crate::{ChatMessage, RoomStatus, TournamentStatus};
+
+pub fn room_stream(room_id: u32) -> StreamName {
+    StreamName(format!("room/{}", room_id).into_bytes())
+}
+
+pub fn lobby_stream() -> StreamName {
+    StreamName(b"lobby".to_vec())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// Room stream. `position` uses the encoding of the stored move history.
+    MoveMade {
+        room_id: u32,
+        player: Option<AccountOwner>,
+        mark: char, // 'X' or 'O'
+        position: u32,
+        move_number: u32, // across all games played in the room
+        timestamp: u64,
+    },
+    /// Room and lobby streams. `result` is 'X', 'O' or 'T' for a draw.
+    GameEnded { room_id: u32, result: char },
+    /// Room and lobby streams; also emitted for the creator of a new room.
+    PlayerJoined {
+        room_id: u32,
+        player: AccountOwner,
+        seat: u8, // 0 = X, 1 = O
+    },
+    /// Room and lobby streams, when the creator cancels a room nobody joined.
+    RoomClosed { room_id: u32 },
+    /// Room and lobby streams; `player` no longer has a seat in the room.
+    PlayerLeft { room_id: u32, player: AccountOwner },
+    /// Room and lobby streams, whenever a room moves to another status.
+    RoomStatusChanged { room_id: u32, status: RoomStatus },
+    /// Room and lobby streams, when a rematch or the next game of a series
+    /// starts in a room that already played. `players` is [X, O].
+    GameStarted {
+        room_id: u32,
+        players: [Option<AccountOwner>; 2],
+    },
+    /// Room stream, including system messages.
+    ChatPosted { room_id: u32, message: ChatMessage },
+    /// Lobby stream, whenever a tournament starts or a match result is reported.
+    TournamentAdvanced {
+        tournament_id: u64,
+        status: TournamentStatus,
+        matches_played: u32,
+        matches_total: u32,
+    },
+}
+
+scalar!(GameEvent);
@@ -1,164 +1,248 @@
-use async_graphql::Response as GraphQLResponse;
-use linera_sdk::{
-    abi::{ContractAbi, ServiceAbi},
-    linera_base_types::AccountOwner,
-};
-use serde::{Deserialize, Serialize};
-
-pub struct InstantTacToeAbi;
-
-impl ContractAbi for InstantTacToeAbi {
-    type Operation = Operation;
-    type Response = Response;
-}
-
-impl ServiceAbi for InstantTacToeAbi {
-    // NOTE: This keeps your "enum query" approach:
-    // Service::handle_query takes Query and returns GraphQLResponse (JSON-packed).
-    type Query = Query;
-    type QueryResponse = GraphQLResponse;
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub enum Response {
-    Ok,
-    OkWithData(String),
-    GameState(GameStateResponse),
-    TournamentCreated { id: u64, name: String },
-    TournamentJoined { id: u64, position: u32 },
-    Error(String),
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct RoomInfo {
-    pub room_id: u32,
-    pub name: String,
-    pub creator: AccountOwner,
-    pub is_full: bool,
-    pub has_password: bool,
-    pub player_count: u8,
-    pub mode: GameMode,
-    pub stake: Option<u64>,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct ChatMessage {
-    pub sender: String,
-    pub text: String,
-    pub timestamp: u64,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub enum GameMode {
-    Classic,
-    Speed(u64),      // Time limit in seconds
-    Tournament(u64), // Tournament ID
-    Ultimate,        // 9x9 board (not implemented yet)
-    PowerUp,         // With special abilities
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
-pub enum TournamentFormat {
-    SingleElimination,
-    Swiss(u32), // Number of rounds
-    RoundRobin,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub enum Operation {
-    // Basic Operations
-    SetNickname { name: String },
-    CreateMatch {
-        room_name: String,
-        password: Option<String>,
-        mode: GameMode,
-        stake: Option<u64>,
-    },
-    JoinGame { room_id: u32, password: Option<String> },
-    MakeMove { room_id: u32, position: u32 },
-    PostMessage { text: String, room_id: u32 },
-    ResetGame { room_id: u32 },
-    LeaveRoom { room_id: u32 },
-    Surrender { room_id: u32 },
-
-    // Tournament Operations
-    CreateTournament {
-        name: String,
-        format: TournamentFormat,
-        entry_fee: Option<u64>,
-        max_players: u32,
-        prize_distribution: Vec<u32>, // Percentages
-    },
-    JoinTournament { tournament_id: u64 },
-    StartTournament { tournament_id: u64 },
-    ReportMatchResult {
-        tournament_id: u64,
-        match_id: u64,
-        result: MatchResult,
-    },
-
-    // Staking & Economy
-    DepositTokens { amount: u64 },
-    WithdrawTokens { amount: u64 },
-    ClaimRewards,
-
-    // Social Features
-    CreateGuild { name: String, tag: String },
-    JoinGuild { guild_id: u64 },
-    InviteToGuild { player: AccountOwner, guild_id: u64 },
-
-    // Advanced Features
-    UsePowerUp { room_id: u32, power_up: PowerUpType },
-    SaveReplay { room_id: u32 },
-    ChallengePlayer { player: AccountOwner, stake: Option<u64> },
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub enum PowerUpType {
-    DoubleMove,
-    Block,
-    Swap,
-    Bomb,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub enum MatchResult {
-    Win(AccountOwner),
-    Loss(AccountOwner),
-    Draw,
-    Forfeit(AccountOwner),
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct GameStateResponse {
-    pub board: [Option<char>; 9],
-    pub players: [Option<AccountOwner>; 2],
-    pub current_player: Option<AccountOwner>,
-    pub winner: Option<char>,
-    pub time_remaining: Option<u64>,
-    pub player_stats: [(u32, u32, u32); 2], // wins, losses, draws
-    pub moves_history: Vec<u32>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub enum Query {
-    GetLobby,
-    GetBoard { room_id: u32 },
-    GetChat { room_id: u32 },
-    GetPlayerStats { player: AccountOwner },
-    GetTournaments { status: Option<TournamentStatus> },
-    GetTournamentDetails { tournament_id: u64 },
-    GetPlayerBalance { player: AccountOwner },
-    GetGuilds,
-    GetGuildDetails { guild_id: u64 },
-    GetReplays { player: AccountOwner },
-    GetLeaderboard { mode: GameMode, limit: u32 },
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
-pub enum TournamentStatus {
-    Registration,
-    InProgress,
-    Completed,
-    Cancelled,
-}
+use async_graphql::{scalar, Enum, Request, Response as GraphQLResponse, SimpleObject};
+use linera_sdk::{
+    abi::{ContractAbi, ServiceAbi},
+    linera_base_types::AccountOwner,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod error;
+pub mod events;
+pub mod notation;
+pub mod rules;
+pub mod solver;
+
+use error::GameError;
+
+pub struct InstantTacToeAbi;
+
+impl ContractAbi for InstantTacToeAbi {
+    type Operation = Operation;
+    type Response = Response;
+}
+
+impl ServiceAbi for InstantTacToeAbi {
+    type Query = Request;
+    type QueryResponse = GraphQLResponse;
+}
+
+/// Application-wide settings, passed as the instantiation argument. Omitted
+/// fields take their default, so `{}` is a valid argument.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct GameConfig {
+    pub abandon_timeout_secs: u64, // idle time before a non-Speed game can be claimed
+    pub replay_retention: u32,     // unpinned replays kept per player
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            abandon_timeout_secs: 60 * 60,
+            replay_retention: 20,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Response {
+    Ok,
+    OkWithData(String),
+    GameState(GameStateResponse),
+    TournamentCreated { id: u64, name: String },
+    TournamentJoined { id: u64, position: u32 },
+    Error(GameError),
+}
+
+/// Lifecycle of a room. Every room operation checks it, and the contract moves
+/// rooms between states only along the edges allowed by `can_transition_to`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum RoomStatus {
+    WaitingForOpponent,
+    InProgress,
+    Finished,
+    Abandoned, // a player left; no further games in this room
+    Closed,    // nobody left in the room
+}
+
+impl RoomStatus {
+    pub fn can_transition_to(self, next: RoomStatus) -> bool {
+        use RoomStatus::*;
+        matches!(
+            (self, next),
+            (WaitingForOpponent, InProgress)
+                | (WaitingForOpponent, Closed)
+                | (InProgress, Finished)
+                | (InProgress, Abandoned)
+                | (Finished, InProgress)
+                | (Finished, Abandoned)
+                | (Abandoned, Closed)
+        )
+    }
+}
+
+impl fmt::Display for RoomStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            RoomStatus::WaitingForOpponent => "waiting for an opponent",
+            RoomStatus::InProgress => "in progress",
+            RoomStatus::Finished => "finished",
+            RoomStatus::Abandoned => "abandoned",
+            RoomStatus::Closed => "closed",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, SimpleObject)]
+pub struct ChatMessage {
+    pub sender: String,
+    pub text: String,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub enum GameMode {
+    Classic,
+    Speed(u64),      // Time bank per player in seconds
+    Tournament(u64), // Tournament ID
+    Ultimate,        // 9x9 meta-board of nine sub-boards
+    PowerUp,         // With special abilities
+}
+
+scalar!(GameMode);
+
+/// Strength of the built-in bot opponent.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard, // perfect play
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Board of an Ultimate game: nine 3x3 sub-boards laid out like the cells of a
+/// 3x3 meta-board. A move in cell `c` sends the opponent to sub-board `c`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, SimpleObject)]
+pub struct UltimateBoard {
+    pub cells: [[Option<char>; 9]; 9],  // [sub_board][cell]
+    pub sub_winners: [Option<char>; 9], // 'X', 'O' or 'T' once a sub-board is closed
+    pub active_board: Option<u32>,      // None = free move on any open sub-board
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub enum TournamentFormat {
+    SingleElimination,
+    Swiss(u32), // Number of rounds
+    RoundRobin,
+}
+
+scalar!(TournamentFormat);
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Operation {
+    // Basic Operations
+    SetNickname { name: String },
+    CreateMatch {
+        room_name: String,
+        password: Option<String>,
+        mode: GameMode,
+        stake: Option<u64>,
+        increment: Option<u64>,     // Fischer increment in seconds, Speed rooms only
+        series_length: Option<u32>, // best-of-N series, N odd
+    },
+    CreateBotMatch { difficulty: Difficulty },
+    JoinGame { room_id: u32, password: Option<String> },
+    MakeMove { room_id: u32, position: u32 },
+    MakeUltimateMove { room_id: u32, board: u32, cell: u32 },
+    PostMessage { text: String, room_id: u32 },
+    OfferRematch { room_id: u32 },
+    AcceptRematch { room_id: u32 },
+    LeaveRoom { room_id: u32 },
+    CancelRoom { room_id: u32 }, // creator only, before anyone joins; refunds the stake
+    Surrender { room_id: u32 },
+    ClaimTimeout { room_id: u32 },
+    OfferDraw { room_id: u32 },
+    AcceptDraw { room_id: u32 },
+    RequestTakeback { room_id: u32 },
+    AcceptTakeback { room_id: u32 },
+
+    // Tournament Operations
+    CreateTournament {
+        name: String,
+        format: TournamentFormat,
+        entry_fee: Option<u64>,
+        max_players: u32,
+        prize_distribution: Vec<u32>, // Percentages
+    },
+    JoinTournament { tournament_id: u64 },
+    StartTournament { tournament_id: u64 },
+    ReportMatchResult {
+        tournament_id: u64,
+        match_id: u64,
+        result: MatchResult,
+    },
+
+    // Staking & Economy, amounts in whole native tokens
+    DepositTokens { amount: u64 },  // from the signer's account on this chain
+    WithdrawTokens { amount: u64 }, // back to the signer's account on this chain
+    ClaimRewards,
+
+    // Social Features
+    CreateGuild { name: String, tag: String },
+    JoinGuild { guild_id: u64 },
+    InviteToGuild { player: AccountOwner, guild_id: u64 },
+
+    // Advanced Features
+    UsePowerUp { room_id: u32, power_up: PowerUpType },
+    SaveReplay { room_id: u32 }, // pins the room's latest replay
+    ChallengePlayer { player: AccountOwner, stake: Option<u64> },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum PowerUpType {
+    DoubleMove,
+    Block,
+    Swap,
+    Bomb,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub enum MatchResult {
+    Win(AccountOwner),
+    Loss(AccountOwner),
+    Draw,
+    Forfeit(AccountOwner),
+}
+
+scalar!(MatchResult);
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GameStateResponse {
+    pub board: [Option<char>; 9],
+    pub players: [Option<AccountOwner>; 2],
+    pub current_player: Option<AccountOwner>,
+    pub winner: Option<char>,
+    pub time_remaining: Option<[u64; 2]>, // microseconds left per player in Speed rooms
+    pub player_stats: [(u32, u32, u32); 2], // wins, losses, draws
+    pub moves_history: Vec<u32>, // Ultimate moves are encoded as board * 9 + cell
+    pub ultimate_board: Option<UltimateBoard>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum TournamentStatus {
+    Registration,
+    InProgress,
+    Completed,
+    Cancelled,
+}
@@ -0,0 +1,450 @@
+//! Portable text notation for finished games, modelled on chess PGN.
+//!
+//! ```text
+//! [Game "InstantTacToe"]
+//! [Mode "Classic"]
+//! [X "alice"]
+//! [O "bob"]
+//! [Result "1-0"]
+//! [Started "1718000000000000"]
+//! [Ended "1718000042000000"]
+//!
+//! 1. a1 b2 2. b1 c3 3. c1 1-0
+//! ```
+//!
+//! Squares are a column letter and a row number, `a1` being the top-left cell
+//! (index 0) and `c3` the bottom-right one (index 8). Ultimate moves name the
+//! sub-board first, then the cell inside it: `b2/a1`. Timestamps are in
+//! microseconds, like everywhere else in the app.
+//!
+//! A result has to agree with the final position when the moves decide the
+//! game. Otherwise any result goes, since games also end by resignation,
+//! timeout or an agreed draw.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    rules::{GameState, RuleError},
+    GameMode,
+};
+
+/// One game in notation form. Headers other than the ones with a field of
+/// their own are kept in `tags`, in order.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub mode: GameMode,
+    pub players: [String; 2], // X, O
+    pub result: Option<char>, // 'X', 'O', 'T', or None while unfinished
+    pub started_at: Option<u64>,
+    pub ended_at: Option<u64>,
+    pub moves: Vec<u32>,
+    pub tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    InvalidHeader(String),
+    MissingMode,
+    InvalidMode(String),
+    InvalidTimestamp(String),
+    InvalidSquare(String),
+    InvalidResult(String),
+    ResultMismatch,
+    IllegalMove { ply: usize, error: RuleError },
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::InvalidHeader(line) => write!(f, "Invalid header line: {}", line),
+            NotationError::MissingMode => write!(f, "Missing Mode header"),
+            NotationError::InvalidMode(mode) => write!(f, "Unknown game mode: {}", mode),
+            NotationError::InvalidTimestamp(value) => write!(f, "Invalid timestamp: {}", value),
+            NotationError::InvalidSquare(square) => write!(f, "Invalid square: {}", square),
+            NotationError::InvalidResult(result) => write!(f, "Invalid result: {}", result),
+            NotationError::ResultMismatch => write!(f, "Result header does not match the move list"),
+            NotationError::IllegalMove { ply, error } => write!(f, "Illegal move at ply {}: {}", ply, error),
+        }
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ultimate = matches!(self.mode, GameMode::Ultimate);
+
+        writeln!(f, "[Game \"InstantTacToe\"]")?;
+        writeln!(f, "[Mode \"{}\"]", format_mode(&self.mode))?;
+        writeln!(f, "[X \"{}\"]", escape(&self.players[0]))?;
+        writeln!(f, "[O \"{}\"]", escape(&self.players[1]))?;
+        writeln!(f, "[Result \"{}\"]", format_result(self.result))?;
+        if let Some(started_at) = self.started_at {
+            writeln!(f, "[Started \"{}\"]", started_at)?;
+        }
+        if let Some(ended_at) = self.ended_at {
+            writeln!(f, "[Ended \"{}\"]", ended_at)?;
+        }
+        for (key, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", key, escape(value))?;
+        }
+        writeln!(f)?;
+
+        let mut tokens = Vec::new();
+        for (ply, position) in self.moves.iter().enumerate() {
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push(format_move(*position, ultimate));
+        }
+        tokens.push(format_result(self.result).to_string());
+        writeln!(f, "{}", tokens.join(" "))
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = NotationError;
+
+    /// Parses a game and checks that its moves are legal and agree with its
+    /// result. PowerUp games are not checked, since power-ups are not part of
+    /// the move list.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut mode = None;
+        let mut players = ["?".to_string(), "?".to_string()];
+        let mut header_result = None;
+        let mut started_at = None;
+        let mut ended_at = None;
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if !line.starts_with('[') {
+                movetext.push_str(line);
+                movetext.push(' ');
+                continue;
+            }
+            let (key, value) = parse_header(line)?;
+            match key.as_str() {
+                "Game" => {}
+                "Mode" => mode = Some(parse_mode(&value)?),
+                "X" => players[0] = value,
+                "O" => players[1] = value,
+                "Result" => header_result = Some(parse_result(&value)?),
+                "Started" => started_at = Some(parse_timestamp(&value)?),
+                "Ended" => ended_at = Some(parse_timestamp(&value)?),
+                _ => tags.push((key, value)),
+            }
+        }
+        let mode = mode.ok_or(NotationError::MissingMode)?;
+        let ultimate = matches!(mode, GameMode::Ultimate);
+
+        let mut moves = Vec::new();
+        let mut movetext_result = None;
+        for token in movetext.split_whitespace() {
+            if token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            if let Ok(result) = parse_result(token) {
+                movetext_result = Some(result);
+                break;
+            }
+            moves.push(parse_move(token, ultimate)?);
+        }
+
+        let result = match (header_result, movetext_result) {
+            (Some(header), Some(movetext)) if header != movetext => return Err(NotationError::ResultMismatch),
+            (header, movetext) => header.or(movetext).flatten(),
+        };
+
+        if !matches!(mode, GameMode::PowerUp) {
+            let mut game = GameState::new(&mode);
+            for (ply, position) in moves.iter().enumerate() {
+                game.apply_move(*position)
+                    .map_err(|error| NotationError::IllegalMove { ply: ply + 1, error })?;
+            }
+            if let Some(outcome) = game.outcome() {
+                if result != Some(outcome.as_char()) {
+                    return Err(NotationError::ResultMismatch);
+                }
+            }
+        }
+
+        Ok(GameRecord {
+            mode,
+            players,
+            result,
+            started_at,
+            ended_at,
+            moves,
+            tags,
+        })
+    }
+}
+
+pub fn format_square(cell: u32) -> String {
+    let column = (b'a' + (cell % 3) as u8) as char;
+    format!("{}{}", column, cell / 3 + 1)
+}
+
+pub fn parse_square(square: &str) -> Result<u32, NotationError> {
+    let invalid = || NotationError::InvalidSquare(square.to_string());
+    let mut chars = square.chars();
+    let (Some(column), Some(row), None) = (chars.next(), chars.next(), chars.next()) else {
+        return Err(invalid());
+    };
+    let column = match column {
+        'a'..='c' => column as u32 - 'a' as u32,
+        _ => return Err(invalid()),
+    };
+    let row = match row {
+        '1'..='3' => row as u32 - '1' as u32,
+        _ => return Err(invalid()),
+    };
+    Ok(row * 3 + column)
+}
+
+/// A stored move index as a square, `board/cell` on Ultimate boards.
+pub fn format_move(position: u32, ultimate: bool) -> String {
+    if ultimate {
+        format!("{}/{}", format_square(position / 9), format_square(position % 9))
+    } else {
+        format_square(position)
+    }
+}
+
+pub fn parse_move(token: &str, ultimate: bool) -> Result<u32, NotationError> {
+    if !ultimate {
+        return parse_square(token);
+    }
+    let Some((board, cell)) = token.split_once('/') else {
+        return Err(NotationError::InvalidSquare(token.to_string()));
+    };
+    Ok(parse_square(board)? * 9 + parse_square(cell)?)
+}
+
+fn format_result(result: Option<char>) -> &'static str {
+    match result {
+        Some('X') => "1-0",
+        Some('O') => "0-1",
+        Some(_) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+fn parse_result(text: &str) -> Result<Option<char>, NotationError> {
+    match text {
+        "1-0" => Ok(Some('X')),
+        "0-1" => Ok(Some('O')),
+        "1/2-1/2" => Ok(Some('T')),
+        "*" => Ok(None),
+        _ => Err(NotationError::InvalidResult(text.to_string())),
+    }
+}
+
+fn format_mode(mode: &GameMode) -> String {
+    match mode {
+        GameMode::Classic => "Classic".to_string(),
+        GameMode::Speed(secs) => format!("Speed {}", secs),
+        GameMode::Tournament(id) => format!("Tournament {}", id),
+        GameMode::Ultimate => "Ultimate".to_string(),
+        GameMode::PowerUp => "PowerUp".to_string(),
+    }
+}
+
+fn parse_mode(text: &str) -> Result<GameMode, NotationError> {
+    let invalid = || NotationError::InvalidMode(text.to_string());
+    let mut parts = text.split_whitespace();
+    let mode = match (parts.next(), parts.next()) {
+        (Some("Classic"), None) => GameMode::Classic,
+        (Some("Ultimate"), None) => GameMode::Ultimate,
+        (Some("PowerUp"), None) => GameMode::PowerUp,
+        (Some("Speed"), Some(secs)) => GameMode::Speed(secs.parse().map_err(|_| invalid())?),
+        (Some("Tournament"), Some(id)) => GameMode::Tournament(id.parse().map_err(|_| invalid())?),
+        _ => return Err(invalid()),
+    };
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(mode)
+}
+
+fn parse_timestamp(text: &str) -> Result<u64, NotationError> {
+    text.parse()
+        .map_err(|_| NotationError::InvalidTimestamp(text.to_string()))
+}
+
+/// Splits `[Key "Value"]` into its key and unescaped value.
+fn parse_header(line: &str) -> Result<(String, String), NotationError> {
+    let invalid = || NotationError::InvalidHeader(line.to_string());
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (key, value) = inner.split_once(' ').ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+    Ok((key.to_string(), unescape(value)))
+}
+
+/// Escapes a header value so it stays on one line.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                other => unescaped.extend(other),
+            },
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(mode: GameMode, moves: Vec<u32>, result: Option<char>) -> GameRecord {
+        GameRecord {
+            mode,
+            players: ["alice".to_string(), "bob".to_string()],
+            result,
+            started_at: Some(1_718_000_000_000_000),
+            ended_at: Some(1_718_000_042_000_000),
+            moves,
+            tags: vec![("Room".to_string(), "7".to_string())],
+        }
+    }
+
+    fn round_trip(record: &GameRecord) -> GameRecord {
+        let parsed: GameRecord = record.to_string().parse().unwrap();
+        assert_eq!(parsed.players, record.players);
+        assert_eq!(parsed.result, record.result);
+        assert_eq!(parsed.started_at, record.started_at);
+        assert_eq!(parsed.ended_at, record.ended_at);
+        assert_eq!(parsed.moves, record.moves);
+        assert_eq!(parsed.tags, record.tags);
+        parsed
+    }
+
+    #[test]
+    fn classic_game_round_trips() {
+        let game = record(GameMode::Classic, vec![0, 3, 1, 4, 2], Some('X'));
+        let text = game.to_string();
+        assert!(text.contains("[Mode \"Classic\"]"));
+        assert!(text.ends_with("1. a1 a2 2. b1 b2 3. c1 1-0\n"));
+
+        let parsed = round_trip(&game);
+        assert!(matches!(parsed.mode, GameMode::Classic));
+    }
+
+    #[test]
+    fn ultimate_game_round_trips() {
+        let game = record(GameMode::Ultimate, vec![4 * 9 + 4, 4 * 9], None);
+        assert!(game.to_string().ends_with("1. b2/b2 b2/a1 *\n"));
+        assert_eq!(parse_move("b2/a1", true), Ok(4 * 9));
+
+        let parsed = round_trip(&game);
+        assert!(matches!(parsed.mode, GameMode::Ultimate));
+    }
+
+    #[test]
+    fn speed_game_round_trips() {
+        let game = record(GameMode::Speed(60), vec![4, 0], None);
+        assert!(game.to_string().contains("[Mode \"Speed 60\"]"));
+
+        let parsed = round_trip(&game);
+        assert!(matches!(parsed.mode, GameMode::Speed(60)));
+    }
+
+    #[test]
+    fn header_values_are_escaped() {
+        let mut game = record(GameMode::Classic, vec![], None);
+        game.players[0] = r#"the "quoted" \ one"#.to_string();
+        game.tags = vec![("Note".to_string(), r#"say "hi""#.to_string())];
+
+        let text = game.to_string();
+        assert!(text.contains(r#"[X "the \"quoted\" \\ one"]"#));
+        assert!(text.contains(r#"[Note "say \"hi\""]"#));
+        round_trip(&game);
+    }
+
+    #[test]
+    fn line_breaks_in_headers_are_escaped() {
+        let mut game = record(GameMode::Classic, vec![], None);
+        game.players[1] = "two\nlines\r".to_string();
+        game.tags = vec![("Note".to_string(), "a\r\nb".to_string())];
+
+        let text = game.to_string();
+        assert!(text.contains(r#"[O "two\nlines\r"]"#));
+        assert!(text.contains(r#"[Note "a\r\nb"]"#));
+        round_trip(&game);
+    }
+
+    #[test]
+    fn conflicting_results_are_rejected() {
+        let text = "[Mode \"Classic\"]\n[Result \"1-0\"]\n\n1. a1 a2 0-1\n";
+        assert_eq!(text.parse::<GameRecord>().err(), Some(NotationError::ResultMismatch));
+    }
+
+    #[test]
+    fn results_must_match_a_decided_board() {
+        // X completes the top row
+        let won = "1. a1 b2 2. b1 c3 3. c1";
+        for result in ["1-0", "0-1", "1/2-1/2", "*"] {
+            let text = format!("[Mode \"Classic\"]\n\n{} {}\n", won, result);
+            let parsed = text.parse::<GameRecord>();
+            if result == "1-0" {
+                assert_eq!(parsed.unwrap().result, Some('X'));
+            } else {
+                assert_eq!(parsed.err(), Some(NotationError::ResultMismatch));
+            }
+        }
+
+        // an undecided board may end by resignation or timeout
+        let text = "[Mode \"Classic\"]\n\n1. b2 a1 2. c3 a3 3. a2 c1 4. b1 1-0\n";
+        assert_eq!(text.parse::<GameRecord>().unwrap().result, Some('X'));
+    }
+
+    #[test]
+    fn illegal_moves_are_rejected() {
+        let text = "[Mode \"Classic\"]\n\n1. b2 b2 *\n";
+        assert_eq!(
+            text.parse::<GameRecord>().err(),
+            Some(NotationError::IllegalMove {
+                ply: 2,
+                error: RuleError::PositionTaken,
+            })
+        );
+
+        let text = "[Mode \"Ultimate\"]\n\n1. b2/c1 a1/a1 *\n";
+        assert_eq!(
+            text.parse::<GameRecord>().err(),
+            Some(NotationError::IllegalMove {
+                ply: 2,
+                error: RuleError::WrongSubBoard(2),
+            })
+        );
+    }
+}
@@ -0,0 +1,508 @@
+//! Chain-independent game rules.
+//!
+//! Everything in here is synchronous and free of views and runtimes, so the
+//! contract, the service, bots and off-chain tools all share one implementation.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameMode, PowerUpType, UltimateBoard};
+
+pub const WIN_LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Board {
+    Classic([Option<char>; 9]),
+    Ultimate(Box<UltimateBoard>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Win(char),
+    Draw,
+}
+
+impl Outcome {
+    /// The result character used across the contract: 'X', 'O' or 'T' for a draw.
+    pub fn as_char(&self) -> char {
+        match self {
+            Outcome::Win(c) => *c,
+            Outcome::Draw => 'T',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    GameOver,
+    PositionOutOfRange,
+    PositionTaken,
+    WrongSubBoard(u32),
+    SubBoardClosed,
+    PowerUpsDisabled,
+    PowerUpNotImplemented,
+    TimeExpired,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::GameOver => write!(f, "Game has finished"),
+            RuleError::PositionOutOfRange => write!(f, "Position out of range"),
+            RuleError::PositionTaken => write!(f, "Position already taken"),
+            RuleError::WrongSubBoard(board) => write!(f, "You must play in sub-board {}", board),
+            RuleError::SubBoardClosed => write!(f, "Sub-board is already closed"),
+            RuleError::PowerUpsDisabled => write!(f, "Power-ups not enabled in this game mode"),
+            RuleError::PowerUpNotImplemented => write!(f, "Power-up not yet implemented"),
+            RuleError::TimeExpired => write!(f, "Time limit exceeded"),
+        }
+    }
+}
+
+/// Board and side to move of a single game.
+///
+/// Moves are plain cell indices: 0-8 on a classic board and `board * 9 + cell`
+/// (0-80) on an Ultimate board, the same encoding as the stored move history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub mode: GameMode,
+    pub board: Board,
+    pub current_player: u8, // 0 = X, 1 = O
+}
+
+impl GameState {
+    pub fn new(mode: &GameMode) -> Self {
+        let board = match mode {
+            GameMode::Ultimate => Board::Ultimate(Box::default()),
+            _ => Board::Classic([None; 9]),
+        };
+        GameState {
+            mode: mode.clone(),
+            board,
+            current_player: 0,
+        }
+    }
+
+    /// Rebuilds a game by replaying `moves` from the starting position.
+    pub fn from_moves(mode: &GameMode, moves: &[u32]) -> Result<Self, RuleError> {
+        let mut game = GameState::new(mode);
+        for position in moves {
+            game.apply_move(*position)?;
+        }
+        Ok(game)
+    }
+
+    pub fn is_ultimate(&self) -> bool {
+        matches!(self.board, Board::Ultimate(_))
+    }
+
+    pub fn ultimate_board(&self) -> Option<&UltimateBoard> {
+        match &self.board {
+            Board::Ultimate(ultimate) => Some(ultimate),
+            Board::Classic(_) => None,
+        }
+    }
+
+    pub fn to_move(&self) -> char {
+        player_char(self.current_player)
+    }
+
+    /// The 3x3 board shown to clients: the cells themselves, or the meta-board
+    /// of sub-board results in Ultimate games.
+    pub fn display_board(&self) -> [Option<char>; 9] {
+        match &self.board {
+            Board::Classic(cells) => *cells,
+            Board::Ultimate(ultimate) => ultimate.sub_winners,
+        }
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        match &self.board {
+            Board::Classic(cells) => board_outcome(cells),
+            Board::Ultimate(ultimate) => {
+                // drawn sub-boards count for nobody on the meta-board
+                let meta = ultimate.sub_winners.map(|w| w.filter(|c| *c != 'T'));
+                if let Some(winner) = check_winner(&meta) {
+                    Some(Outcome::Win(winner))
+                } else if ultimate.sub_winners.iter().all(Option::is_some) {
+                    Some(Outcome::Draw)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<u32> {
+        if self.outcome().is_some() {
+            return Vec::new();
+        }
+        match &self.board {
+            Board::Classic(cells) => (0..9u32).filter(|p| cells[*p as usize].is_none()).collect(),
+            Board::Ultimate(ultimate) => {
+                let boards: Vec<u32> = match ultimate.active_board {
+                    Some(active) => vec![active],
+                    None => (0..9u32)
+                        .filter(|b| ultimate.sub_winners[*b as usize].is_none())
+                        .collect(),
+                };
+                boards
+                    .into_iter()
+                    .flat_map(|b| {
+                        (0..9u32)
+                            .filter(move |c| ultimate.cells[b as usize][*c as usize].is_none())
+                            .map(move |c| b * 9 + c)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Plays `position` for the side to move. Returns the outcome if the move
+    /// ended the game; otherwise the turn passes to the opponent.
+    pub fn apply_move(&mut self, position: u32) -> Result<Option<Outcome>, RuleError> {
+        if self.outcome().is_some() {
+            return Err(RuleError::GameOver);
+        }
+        let mark = self.to_move();
+
+        match &mut self.board {
+            Board::Classic(cells) => {
+                let pos = position as usize;
+                if pos >= 9 {
+                    return Err(RuleError::PositionOutOfRange);
+                }
+                if cells[pos].is_some() {
+                    return Err(RuleError::PositionTaken);
+                }
+                cells[pos] = Some(mark);
+            }
+            Board::Ultimate(ultimate) => {
+                if position >= 81 {
+                    return Err(RuleError::PositionOutOfRange);
+                }
+                let (board, cell) = (position / 9, position % 9);
+                let (b, c) = (board as usize, cell as usize);
+                if let Some(active) = ultimate.active_board {
+                    if active != board {
+                        return Err(RuleError::WrongSubBoard(active));
+                    }
+                }
+                if ultimate.sub_winners[b].is_some() {
+                    return Err(RuleError::SubBoardClosed);
+                }
+                if ultimate.cells[b][c].is_some() {
+                    return Err(RuleError::PositionTaken);
+                }
+
+                ultimate.cells[b][c] = Some(mark);
+                ultimate.sub_winners[b] = board_outcome(&ultimate.cells[b]).map(|o| o.as_char());
+
+                // the opponent is sent to the matching sub-board, unless it is already closed
+                ultimate.active_board = if ultimate.sub_winners[c].is_none() { Some(cell) } else { None };
+            }
+        }
+
+        let outcome = self.outcome();
+        if outcome.is_none() {
+            self.current_player = 1 - self.current_player;
+        }
+        Ok(outcome)
+    }
+
+    /// Applies a power-up for the side to move. Power-ups do not pass the turn.
+    pub fn apply_power_up(&mut self, power_up: &PowerUpType) -> Result<(), RuleError> {
+        if !matches!(self.mode, GameMode::PowerUp) {
+            return Err(RuleError::PowerUpsDisabled);
+        }
+        if self.outcome().is_some() {
+            return Err(RuleError::GameOver);
+        }
+        let Board::Classic(cells) = &mut self.board else {
+            return Err(RuleError::PowerUpsDisabled);
+        };
+
+        match power_up {
+            PowerUpType::DoubleMove | PowerUpType::Block => Err(RuleError::PowerUpNotImplemented),
+            PowerUpType::Swap => {
+                for cell in cells.iter_mut() {
+                    *cell = match *cell {
+                        Some('X') => Some('O'),
+                        Some('O') => Some('X'),
+                        other => other,
+                    };
+                }
+                Ok(())
+            }
+            PowerUpType::Bomb => {
+                *cells = [None; 9];
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Longest time bank a Speed room can be created with.
+pub const MAX_TIME_BANK_SECS: u64 = 24 * 60 * 60;
+/// Longest Fischer increment a Speed room can be created with.
+pub const MAX_INCREMENT_SECS: u64 = 60 * 60;
+
+/// Per-player time banks of a Speed game with an optional Fischer increment.
+/// All values are in microseconds, like the runtime's timestamps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChessClock {
+    pub initial: u64,
+    pub increment: u64,
+    pub remaining: [u64; 2],
+    pub running: Option<u8>, // player whose clock is ticking
+    pub since: u64,          // when the running clock was last started
+}
+
+impl ChessClock {
+    pub fn new(base_secs: u64, increment_secs: u64) -> Self {
+        let initial = base_secs.saturating_mul(1_000_000);
+        ChessClock {
+            initial,
+            increment: increment_secs.saturating_mul(1_000_000),
+            remaining: [initial; 2],
+            running: None,
+            since: 0,
+        }
+    }
+
+    /// Refills both banks and stops the clock, e.g. for a new game in the same room.
+    pub fn reset(&mut self) {
+        self.remaining = [self.initial; 2];
+        self.running = None;
+        self.since = 0;
+    }
+
+    pub fn start(&mut self, player: u8, now: u64) {
+        self.running = Some(player);
+        self.since = now;
+    }
+
+    pub fn remaining_at(&self, player: u8, now: u64) -> u64 {
+        let bank = self.remaining[player as usize];
+        if self.running == Some(player) {
+            bank.saturating_sub(now.saturating_sub(self.since))
+        } else {
+            bank
+        }
+    }
+
+    pub fn remaining_all(&self, now: u64) -> [u64; 2] {
+        [self.remaining_at(0, now), self.remaining_at(1, now)]
+    }
+
+    /// The player whose time has run out, if any.
+    pub fn flagged(&self, now: u64) -> Option<u8> {
+        self.running.filter(|p| self.remaining_at(*p, now) == 0)
+    }
+
+    /// Stops the clock of the running player, charging the time used.
+    pub fn stop(&mut self, now: u64) {
+        if let Some(player) = self.running {
+            self.remaining[player as usize] = self.remaining_at(player, now);
+            self.running = None;
+        }
+    }
+
+    /// Ends the running player's turn: charges the time used, adds the increment
+    /// and starts the opponent's clock. Fails if the time ran out first.
+    pub fn press(&mut self, now: u64) -> Result<(), RuleError> {
+        let Some(player) = self.running else {
+            return Ok(());
+        };
+        self.stop(now);
+        let bank = &mut self.remaining[player as usize];
+        if *bank == 0 {
+            return Err(RuleError::TimeExpired);
+        }
+        *bank = bank.saturating_add(self.increment);
+        self.start(1 - player, now);
+        Ok(())
+    }
+}
+
+pub fn player_char(idx: u8) -> char {
+    if idx == 0 {
+        'X'
+    } else {
+        'O'
+    }
+}
+
+pub fn check_winner(board: &[Option<char>; 9]) -> Option<char> {
+    for [a, b, c] in WIN_LINES {
+        if board[a].is_some() && board[a] == board[b] && board[a] == board[c] {
+            return board[a];
+        }
+    }
+    None
+}
+
+/// Outcome of a single 3x3 board: a completed line, or a draw once it is full.
+pub fn board_outcome(board: &[Option<char>; 9]) -> Option<Outcome> {
+    if let Some(winner) = check_winner(board) {
+        Some(Outcome::Win(winner))
+    } else if board.iter().all(Option::is_some) {
+        Some(Outcome::Draw)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Option<char> = Some('X');
+    const O: Option<char> = Some('O');
+    const T: Option<char> = Some('T');
+
+    fn ultimate(game: &GameState) -> &UltimateBoard {
+        game.ultimate_board().unwrap()
+    }
+
+    #[test]
+    fn every_line_wins() {
+        for line in WIN_LINES {
+            let mut board = [None; 9];
+            for cell in line {
+                board[cell] = O;
+            }
+            assert_eq!(check_winner(&board), O, "line {:?}", line);
+            assert_eq!(board_outcome(&board), Some(Outcome::Win('O')));
+        }
+    }
+
+    #[test]
+    fn full_board_without_a_line_is_a_draw() {
+        let board = [X, O, X, X, O, O, O, X, X];
+        assert_eq!(check_winner(&board), None);
+        assert_eq!(board_outcome(&board), Some(Outcome::Draw));
+
+        let mut open = board;
+        open[8] = None;
+        assert_eq!(board_outcome(&open), None);
+    }
+
+    #[test]
+    fn ultimate_move_sends_opponent_to_matching_sub_board() {
+        let mut game = GameState::new(&GameMode::Ultimate);
+        assert_eq!(game.apply_move(4 * 9 + 2), Ok(None));
+        assert_eq!(ultimate(&game).active_board, Some(2));
+        assert_eq!(game.to_move(), 'O');
+
+        assert_eq!(game.apply_move(5 * 9), Err(RuleError::WrongSubBoard(2)));
+        assert!(game.legal_moves().iter().all(|m| m / 9 == 2));
+        assert_eq!(game.apply_move(2 * 9 + 4), Ok(None));
+        assert_eq!(ultimate(&game).active_board, Some(4));
+    }
+
+    #[test]
+    fn ultimate_move_into_closed_sub_board_gives_a_free_move() {
+        let mut board = UltimateBoard {
+            active_board: Some(0),
+            ..Default::default()
+        };
+        board.sub_winners[2] = X;
+        let mut game = GameState {
+            mode: GameMode::Ultimate,
+            board: Board::Ultimate(Box::new(board)),
+            current_player: 0,
+        };
+
+        assert_eq!(game.apply_move(2), Ok(None));
+        assert_eq!(ultimate(&game).active_board, None);
+        // every open cell of every open sub-board: 8 boards, one cell taken
+        let moves = game.legal_moves();
+        assert_eq!(moves.len(), 8 * 9 - 1);
+        assert!(moves.iter().all(|m| m / 9 != 2));
+        assert_eq!(game.apply_move(2 * 9), Err(RuleError::SubBoardClosed));
+    }
+
+    #[test]
+    fn drawn_sub_boards_count_for_nobody_on_the_meta_board() {
+        let board = UltimateBoard {
+            sub_winners: [T, T, T, X, O, X, O, X, None],
+            ..Default::default()
+        };
+        let mut game = GameState {
+            mode: GameMode::Ultimate,
+            board: Board::Ultimate(Box::new(board)),
+            current_player: 0,
+        };
+        // a row of drawn sub-boards is not a line
+        assert_eq!(game.outcome(), None);
+
+        if let Board::Ultimate(board) = &mut game.board {
+            board.sub_winners[8] = O;
+        }
+        assert_eq!(game.outcome(), Some(Outcome::Draw));
+        assert_eq!(game.apply_move(0), Err(RuleError::GameOver));
+    }
+
+    #[test]
+    fn clock_charges_the_mover_and_adds_the_increment() {
+        let mut clock = ChessClock::new(10, 2);
+        clock.start(0, 0);
+        assert_eq!(clock.press(3_000_000), Ok(()));
+        assert_eq!(clock.remaining, [9_000_000, 10_000_000]);
+        assert_eq!(clock.running, Some(1));
+        assert_eq!(clock.remaining_all(4_000_000), [9_000_000, 9_000_000]);
+    }
+
+    #[test]
+    fn clock_flags_the_running_player_only_once_the_bank_is_empty() {
+        let mut clock = ChessClock::new(10, 0);
+        clock.start(0, 0);
+        assert_eq!(clock.flagged(9_999_999), None);
+        assert_eq!(clock.flagged(10_000_000), Some(0));
+        assert_eq!(clock.press(12_000_000), Err(RuleError::TimeExpired));
+        assert_eq!(clock.remaining[0], 0);
+
+        clock.stop(12_000_000);
+        assert_eq!(clock.flagged(20_000_000), None);
+    }
+
+    #[test]
+    fn clock_saturates_instead_of_wrapping() {
+        let mut clock = ChessClock::new(u64::MAX, u64::MAX);
+        assert_eq!(clock.initial, u64::MAX);
+        clock.start(0, 0);
+        assert_eq!(clock.press(1), Ok(()));
+        assert_eq!(clock.remaining[0], u64::MAX);
+    }
+
+    #[test]
+    fn from_moves_replays_a_game() {
+        let game = GameState::from_moves(&GameMode::Classic, &[0, 3, 1, 4, 2]).unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Win('X')));
+        assert_eq!(game.display_board(), [X, X, X, O, O, None, None, None, None]);
+
+        let game = GameState::from_moves(&GameMode::Ultimate, &[4 * 9 + 4, 4 * 9]).unwrap();
+        assert_eq!(ultimate(&game).active_board, Some(0));
+        assert_eq!(game.to_move(), 'X');
+    }
+
+    #[test]
+    fn from_moves_rejects_an_illegal_move() {
+        let result = GameState::from_moves(&GameMode::Classic, &[4, 4]);
+        assert_eq!(result.err(), Some(RuleError::PositionTaken));
+
+        let result = GameState::from_moves(&GameMode::Classic, &[0, 3, 1, 4, 2, 5]);
+        assert_eq!(result.err(), Some(RuleError::GameOver));
+    }
+}
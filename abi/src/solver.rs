@@ -0,0 +1,298 @@
+//! Exhaustive game-tree solver for the classic 3x3 board.
+//!
+//! The whole tree has only a few thousand distinct positions, so every value
+//! here is exact. Ultimate boards are far too large for a full solve and are
+//! not handled.
+
+use std::collections::HashMap;
+
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rules::{board_outcome, Outcome},
+    Difficulty,
+};
+
+/// Game-theoretic value of a position for the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[graphql(name = "GameValue")]
+pub enum Value {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// A solved position: its value and the number of plies until the result with
+/// best play from both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Solution {
+    pub value: Value,
+    pub plies: u32,
+}
+
+impl Solution {
+    /// Ranks solutions for the side to move: quicker wins and slower losses
+    /// score higher.
+    pub fn score(&self) -> i32 {
+        match self.value {
+            Value::Win => 100 - self.plies as i32,
+            Value::Draw => 0,
+            Value::Loss => self.plies as i32 - 100,
+        }
+    }
+
+    /// The same result seen by the player whose move led to this position.
+    fn for_mover(&self) -> Solution {
+        let value = match self.value {
+            Value::Win => Value::Loss,
+            Value::Draw => Value::Draw,
+            Value::Loss => Value::Win,
+        };
+        Solution {
+            value,
+            plies: self.plies + 1,
+        }
+    }
+}
+
+/// Value of playing `position`, for the player who plays it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveEval {
+    pub position: u32,
+    pub solution: Solution,
+}
+
+/// How a played move compares with the best move available in the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum MoveLabel {
+    Best,
+    Inaccuracy, // keeps the result, but wins more slowly or loses sooner
+    Blunder,    // gives away a win or a draw
+}
+
+impl MoveLabel {
+    /// Labels `played` against the evaluations of every legal move.
+    pub fn classify(evals: &[MoveEval], played: u32) -> Option<MoveLabel> {
+        let best = evals.iter().map(|eval| eval.solution).max_by_key(Solution::score)?;
+        let played = evals.iter().find(|eval| eval.position == played)?.solution;
+        Some(if played.score() == best.score() {
+            MoveLabel::Best
+        } else if played.value == best.value {
+            MoveLabel::Inaccuracy
+        } else {
+            MoveLabel::Blunder
+        })
+    }
+}
+
+/// Solves positions with a transposition table, so one solver can be reused
+/// across the plies of a game.
+#[derive(Debug, Default)]
+pub struct Solver {
+    cache: HashMap<u32, Solution>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Solver::default()
+    }
+
+    pub fn solve(&mut self, cells: &[Option<char>; 9], to_move: char) -> Solution {
+        if let Some(outcome) = board_outcome(cells) {
+            let value = match outcome {
+                Outcome::Win(c) if c == to_move => Value::Win,
+                Outcome::Win(_) => Value::Loss,
+                Outcome::Draw => Value::Draw,
+            };
+            return Solution { value, plies: 0 };
+        }
+
+        let key = position_key(cells, to_move);
+        if let Some(solution) = self.cache.get(&key) {
+            return *solution;
+        }
+
+        let solution = self
+            .evaluate_moves(cells, to_move)
+            .into_iter()
+            .map(|eval| eval.solution)
+            .max_by_key(Solution::score)
+            .expect("a board without an outcome has an empty cell");
+        self.cache.insert(key, solution);
+        solution
+    }
+
+    /// Every legal move with its value; empty once the game is over.
+    pub fn evaluate_moves(&mut self, cells: &[Option<char>; 9], to_move: char) -> Vec<MoveEval> {
+        if board_outcome(cells).is_some() {
+            return Vec::new();
+        }
+        let opponent = if to_move == 'X' { 'O' } else { 'X' };
+
+        let mut evals = Vec::new();
+        for position in 0..9 {
+            if cells[position].is_some() {
+                continue;
+            }
+            let mut child = *cells;
+            child[position] = Some(to_move);
+            evals.push(MoveEval {
+                position: position as u32,
+                solution: self.solve(&child, opponent).for_mover(),
+            });
+        }
+        evals
+    }
+
+    /// All moves that reach the best achievable result in the best way.
+    pub fn best_moves(&mut self, cells: &[Option<char>; 9], to_move: char) -> Vec<u32> {
+        let evals = self.evaluate_moves(cells, to_move);
+        let Some(best) = evals.iter().map(|eval| eval.solution.score()).max() else {
+            return Vec::new();
+        };
+        evals
+            .into_iter()
+            .filter(|eval| eval.solution.score() == best)
+            .map(|eval| eval.position)
+            .collect()
+    }
+}
+
+/// Picks a move for the built-in bot. Hard always plays a best move; the
+/// easier levels play a random legal move some of the time instead.
+///
+/// The contract has no randomness, so `seed` should differ from move to move,
+/// e.g. be derived from the block time.
+pub fn bot_move(cells: &[Option<char>; 9], to_move: char, difficulty: Difficulty, seed: u64) -> Option<u32> {
+    let mistake_percent = match difficulty {
+        Difficulty::Easy => 60,
+        Difficulty::Medium => 25,
+        Difficulty::Hard => 0,
+    };
+
+    if board_outcome(cells).is_some() {
+        return None;
+    }
+    let roll = mix(seed);
+    let pick = mix(roll);
+    let candidates: Vec<u32> = if roll % 100 < mistake_percent {
+        (0..9u32).filter(|p| cells[*p as usize].is_none()).collect()
+    } else {
+        Solver::new().best_moves(cells, to_move)
+    };
+    Some(candidates[(pick % candidates.len() as u64) as usize])
+}
+
+/// Base-3 encoding of the cells, plus the side to move in the lowest bit.
+fn position_key(cells: &[Option<char>; 9], to_move: char) -> u32 {
+    let key = cells.iter().fold(0u32, |key, cell| {
+        key * 3
+            + match cell {
+                None => 0,
+                Some('X') => 1,
+                Some(_) => 2,
+            }
+    });
+    key * 2 + u32::from(to_move != 'X')
+}
+
+/// SplitMix64 finalizer, enough to spread a timestamp over all bits.
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Option<char> = Some('X');
+    const O: Option<char> = Some('O');
+
+    #[test]
+    fn empty_board_is_a_draw_in_nine_plies() {
+        let solution = Solver::new().solve(&[None; 9], 'X');
+        assert_eq!(
+            solution,
+            Solution {
+                value: Value::Draw,
+                plies: 9
+            }
+        );
+    }
+
+    #[test]
+    fn finds_the_winning_move() {
+        // X to move completes the top row
+        let cells = [X, X, None, O, O, None, None, None, None];
+        let mut solver = Solver::new();
+        assert_eq!(
+            solver.solve(&cells, 'X'),
+            Solution {
+                value: Value::Win,
+                plies: 1
+            }
+        );
+        assert_eq!(solver.best_moves(&cells, 'X'), vec![2]);
+
+        // with O to move instead, O wins on the middle row
+        assert_eq!(
+            solver.solve(&cells, 'O'),
+            Solution {
+                value: Value::Win,
+                plies: 1
+            }
+        );
+        assert_eq!(solver.best_moves(&cells, 'O'), vec![5]);
+    }
+
+    #[test]
+    fn classify_labels_moves_against_the_best_one() {
+        let cells = [X, X, None, O, O, None, None, None, None];
+        let evals = Solver::new().evaluate_moves(&cells, 'X');
+        assert_eq!(MoveLabel::classify(&evals, 2), Some(MoveLabel::Best));
+        // letting O complete the middle row throws the win away
+        assert_eq!(MoveLabel::classify(&evals, 8), Some(MoveLabel::Blunder));
+        assert_eq!(MoveLabel::classify(&evals, 0), None);
+    }
+
+    /// Plays every possible game against the Hard bot, the opponent trying
+    /// each legal move in turn; returns whether the bot ever lost.
+    fn hard_bot_loses(cells: [Option<char>; 9], to_move: char, bot: char, seed: u64) -> bool {
+        match board_outcome(&cells) {
+            Some(Outcome::Win(winner)) => return winner != bot,
+            Some(Outcome::Draw) => return false,
+            None => {}
+        }
+        let next = if to_move == 'X' { 'O' } else { 'X' };
+        if to_move == bot {
+            let position = bot_move(&cells, bot, Difficulty::Hard, seed).unwrap();
+            let mut child = cells;
+            child[position as usize] = Some(bot);
+            return hard_bot_loses(child, next, bot, mix(seed));
+        }
+        (0..9).filter(|p| cells[*p].is_none()).any(|position| {
+            let mut child = cells;
+            child[position] = Some(to_move);
+            hard_bot_loses(child, next, bot, seed)
+        })
+    }
+
+    #[test]
+    fn hard_bot_never_loses() {
+        for bot in ['X', 'O'] {
+            for seed in 0..3 {
+                assert!(!hard_bot_loses([None; 9], 'X', bot, seed), "bot as {}", bot);
+            }
+        }
+    }
+
+    #[test]
+    fn bot_has_no_move_once_the_game_is_over() {
+        let cells = [X, X, X, O, O, None, None, None, None];
+        assert_eq!(bot_move(&cells, 'O', Difficulty::Hard, 0), None);
+    }
+}
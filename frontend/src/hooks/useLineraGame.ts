@@ -2,9 +2,26 @@
 import { useState, useEffect, useCallback } from 'react';
 import { useDynamicContext } from '@dynamic-labs/sdk-react-core';
 import { lineraAdapter } from '../utils/linera-adapter';
-import type { GameState } from '../types';
+import {
+  GUILDS_QUERY,
+  LOBBY_QUERY,
+  ROOM_QUERY,
+  TOURNAMENTS_QUERY,
+  isOpenRoom,
+  toGameState,
+  toRoom,
+  toTournament,
+} from '../utils/queries';
+import type { GraphQLGuild, GraphQLRoom, GraphQLTournament } from '../utils/queries';
+import type { GameState, Guild, Room, Tournament } from '../types';
 import toast from 'react-hot-toast';
 
+// Rooms still being played or waiting for an opponent.
+const fetchLobby = async (): Promise<Room[]> => {
+  const response = await lineraAdapter.queryApplication<{ rooms: GraphQLRoom[] }>(LOBBY_QUERY);
+  return response.rooms.filter(isOpenRoom).map(toRoom);
+};
+
 export const useLineraGame = () => {
   const { primaryWallet, user } = useDynamicContext();
 
@@ -15,9 +32,9 @@ export const useLineraGame = () => {
   const [nickname, setNickname] = useState('');
 
   // Loaded data from contract
-  const [lobby, setLobby] = useState<any[]>([]);
-  const [tournaments, setTournaments] = useState<any[]>([]);
-  const [guilds, setGuilds] = useState<any[]>([]);
+  const [lobby, setLobby] = useState<Room[]>([]);
+  const [tournaments, setTournaments] = useState<Tournament[]>([]);
+  const [guilds, setGuilds] = useState<Guild[]>([]);
   const [isLoadingInitial, setIsLoadingInitial] = useState(false);
 
   // Load lobby, tournaments, guilds on connect
@@ -28,24 +45,20 @@ export const useLineraGame = () => {
 
     try {
       // Load open games / lobby
-      const lobbyData = await lineraAdapter.queryApplication<any[]>({
-        type: 'GetLobby',
-      });
-      setLobby(lobbyData || []);
+      const lobbyData = await fetchLobby();
+      setLobby(lobbyData);
       console.log("Lobby loaded:", lobbyData);
 
       // Load active tournaments
-      const tournamentsData = await lineraAdapter.queryApplication<any[]>({
-        type: 'GetTournaments',
-      });
-      setTournaments(tournamentsData || []);
+      const tournamentsData = await lineraAdapter.queryApplication<{ tournaments: GraphQLTournament[] }>(
+        TOURNAMENTS_QUERY
+      );
+      setTournaments(tournamentsData.tournaments.map(toTournament));
       console.log("Tournaments loaded:", tournamentsData);
 
       // Load available guilds
-      const guildsData = await lineraAdapter.queryApplication<any[]>({
-        type: 'GetGuilds',
-      });
-      setGuilds(guildsData || []);
+      const guildsData = await lineraAdapter.queryApplication<{ guilds: GraphQLGuild[] }>(GUILDS_QUERY);
+      setGuilds(guildsData.guilds.map(({ memberCount, ...guild }) => ({ ...guild, members: memberCount })));
       console.log("Guilds loaded:", guildsData);
 
     } catch (error) {
@@ -201,23 +214,19 @@ export const useLineraGame = () => {
     }
   };
 
-  const getLobby = async (): Promise<any[]> => {
+  const getLobby = async (): Promise<Room[]> => {
     try {
-      const res = await lineraAdapter.queryApplication({ type: 'GetLobby' });
-      return Array.isArray(res) ? res : [];
+      return await fetchLobby();
     } catch (error) {
       toast.error('Failed to fetch lobby');
       return [];
     }
   };
 
-  const getGameState = async (roomId: number): Promise<any | null> => {
+  const getGameState = async (roomId: number): Promise<GameState | null> => {
     try {
-      const response = await lineraAdapter.queryApplication<GameState>({
-        type: 'GetBoard',
-        room_id: roomId
-      });
-      return response || null;
+      const response = await lineraAdapter.queryApplication<{ room: GraphQLRoom }>(ROOM_QUERY, { roomId });
+      return toGameState(response.room);
     } catch (error) {
       console.error('Failed to fetch game state:', error);
       toast.error('Failed to fetch game state');
@@ -2,9 +2,15 @@ import { initialize, Faucet, Client, Wallet, Application } from "@linera/client"
 import type { Wallet as DynamicWallet } from "@dynamic-labs/sdk-react-core";
 import { DynamicSigner } from "./dynamic-signer";
 import { LINERA_RPC_URL, CONTRACT_APP_ID } from "../constants";
+import { PLAYER_BALANCE_QUERY } from "./queries";
 
-type GetPlayerBalanceResponse = {
-  balance?: number;
+type GraphQLResponse<T> = {
+  data?: T;
+  errors?: { message: string }[];
+};
+
+type PlayerBalanceResponse = {
+  player?: { balance: number };
 };
 
 export interface LineraProvider {
@@ -109,10 +115,12 @@ export class LineraAdapter {
     this.onConnectionChange?.();
   }
 
-  async queryApplication<T>(query: any): Promise<T> {
+  async queryApplication<T>(query: string, variables?: Record<string, unknown>): Promise<T> {
   if (!this.application) throw new Error("Application not set");
-  const result = await this.application.query(JSON.stringify(query));
-  return JSON.parse(result) as T;
+  const result = await this.application.query(JSON.stringify({ query, variables }));
+  const response: GraphQLResponse<T> = JSON.parse(result);
+  if (response.errors?.length) throw new Error(response.errors[0].message);
+  return response.data as T;
 }
 
 async executeOperation(operation: any): Promise<any> {
@@ -142,12 +150,12 @@ async getBalance(): Promise<number> {
   const addr = this.address;
   if (!addr) return 0;
 
-  const response = await this.queryApplication<GetPlayerBalanceResponse>({
-    type: "GetPlayerBalance",
-    player: addr,
-  });
+  const response = await this.queryApplication<PlayerBalanceResponse>(
+    PLAYER_BALANCE_QUERY,
+    { owner: addr }
+  );
 
-  return response?.balance ?? 0;
+  return response?.player?.balance ?? 0;
 }
 
 
@@ -0,0 +1,206 @@
+// GraphQL documents for the game service, and mappers from their results to
+// the shapes the UI works with.
+import type { ChatMessage, GameMode, GameState, Room, Tournament, TournamentStatus } from '../types';
+
+const ROOM_FIELDS = `
+  id
+  name
+  creator { account nickname }
+  hasPassword
+  status
+  mode
+  stake
+  playerCount
+  isFull
+`;
+
+const TOURNAMENT_FIELDS = `
+  id
+  name
+  format
+  status
+  entryFee
+  prizePool
+  players { account }
+  bracket { round result }
+`;
+
+export const LOBBY_QUERY = `
+  query {
+    rooms { ${ROOM_FIELDS} }
+  }
+`;
+
+export const ROOM_QUERY = `
+  query Room($roomId: Int!) {
+    room(roomId: $roomId) {
+      ${ROOM_FIELDS}
+      players { account nickname stats { wins losses draws } }
+      board { cells toMove currentPlayer winner moves timeRemaining }
+    }
+  }
+`;
+
+export const CHAT_QUERY = `
+  query Chat($roomId: Int!, $limit: Int) {
+    room(roomId: $roomId) {
+      chat(limit: $limit) { sender text timestamp }
+    }
+  }
+`;
+
+export const TOURNAMENTS_QUERY = `
+  query Tournaments($status: TournamentStatus) {
+    tournaments(status: $status) { ${TOURNAMENT_FIELDS} }
+  }
+`;
+
+export const TOURNAMENT_QUERY = `
+  query Tournament($tournamentId: Int!) {
+    tournament(tournamentId: $tournamentId) { ${TOURNAMENT_FIELDS} }
+  }
+`;
+
+export const GUILDS_QUERY = `
+  query {
+    guilds { id name tag level memberCount treasury }
+  }
+`;
+
+export const PLAYER_BALANCE_QUERY = `
+  query PlayerBalance($owner: AccountOwner!) {
+    player(owner: $owner) { balance }
+  }
+`;
+
+type GraphQLPlayer = {
+  account: string;
+  nickname?: string;
+  stats?: { wins: number; losses: number; draws: number };
+};
+
+export type GraphQLRoom = {
+  id: number;
+  name: string;
+  creator: GraphQLPlayer;
+  hasPassword: boolean;
+  status: string;
+  mode: string | Record<string, number>;
+  stake: number | null;
+  playerCount: number;
+  isFull: boolean;
+  players?: (GraphQLPlayer | null)[];
+  board?: {
+    cells: (string | null)[];
+    toMove: string;
+    currentPlayer: string | null;
+    winner: string | null;
+    moves: number[];
+    timeRemaining: [number, number] | null;
+  };
+};
+
+export type GraphQLTournament = {
+  id: number;
+  name: string;
+  format: string | Record<string, number>;
+  status: string;
+  entryFee: number | null;
+  prizePool: number;
+  players: GraphQLPlayer[];
+  bracket: { round: number; result: unknown }[];
+};
+
+export type GraphQLGuild = {
+  id: number;
+  name: string;
+  tag: string;
+  level: number;
+  memberCount: number;
+  treasury: number;
+};
+
+// Modes carrying data (`Speed`, `Tournament`) come back as `{ "Speed": 60 }`.
+const variantName = (value: string | Record<string, number>): string =>
+  typeof value === 'string' ? value : Object.keys(value)[0];
+
+// GraphQL enum values are SCREAMING_CASE; the UI uses the Rust variant names.
+const TOURNAMENT_STATUS: Record<string, TournamentStatus> = {
+  REGISTRATION: 'Registration',
+  IN_PROGRESS: 'InProgress',
+  COMPLETED: 'Completed',
+};
+
+export const toTournamentStatusEnum = (status?: string): string | undefined =>
+  Object.keys(TOURNAMENT_STATUS).find((key) => TOURNAMENT_STATUS[key] === status);
+
+export const isOpenRoom = (room: GraphQLRoom): boolean =>
+  room.status === 'WAITING_FOR_OPPONENT' || room.status === 'IN_PROGRESS';
+
+export const toRoom = (room: GraphQLRoom): Room => ({
+  roomId: room.id,
+  name: room.name,
+  creator: room.creator.nickname || room.creator.account,
+  isFull: room.isFull,
+  hasPassword: room.hasPassword,
+  playerCount: room.playerCount,
+  mode: variantName(room.mode) as GameMode,
+  stake: room.stake ?? undefined,
+});
+
+export const toGameState = (room: GraphQLRoom): GameState | null => {
+  const board = room.board;
+  if (!board) return null;
+
+  const seats = room.players ?? [];
+  const nicknames: Record<string, string> = {};
+  for (const player of seats) {
+    if (player?.nickname) nicknames[player.account] = player.nickname;
+  }
+
+  // The clock is in microseconds, one entry per seat.
+  const seat = board.toMove === 'X' ? 0 : 1;
+  const timeRemaining = board.timeRemaining
+    ? Math.floor(board.timeRemaining[seat] / 1_000_000)
+    : undefined;
+
+  return {
+    board: board.cells,
+    players: seats.map((player) => player?.account ?? null),
+    current_player: board.currentPlayer,
+    winner: board.winner,
+    time_remaining: timeRemaining,
+    player_stats: seats.map((player): [number, number, number] => {
+      const stats = player?.stats;
+      return [stats?.wins ?? 0, stats?.losses ?? 0, stats?.draws ?? 0];
+    }),
+    moves_history: board.moves,
+    player_nicknames: nicknames,
+    mode: variantName(room.mode),
+    stake: room.stake ?? undefined,
+  };
+};
+
+export const toTournament = (tournament: GraphQLTournament): Tournament => {
+  const pending = tournament.bracket.filter((match) => match.result == null);
+  const currentRound = pending.length
+    ? Math.min(...pending.map((match) => match.round))
+    : tournament.bracket.reduce((round, match) => Math.max(round, match.round), 0);
+
+  return {
+    id: tournament.id,
+    name: tournament.name,
+    format: variantName(tournament.format) as Tournament['format'],
+    status: TOURNAMENT_STATUS[tournament.status] ?? 'Registration',
+    entryFee: tournament.entryFee ?? undefined,
+    prizePool: tournament.prizePool,
+    players: tournament.players.map((player) => player.account),
+    maxPlayers: 32, // not exposed by the service
+    currentRound,
+  };
+};
+
+export const toChatMessage = (message: Omit<ChatMessage, 'isSystem'>): ChatMessage => ({
+  ...message,
+  isSystem: message.sender === 'SYSTEM',
+});
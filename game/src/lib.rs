@@ -1,1680 +1,2633 @@
-use async_graphql::{Response as GraphQLResponse, Value};
-use linera_sdk::{
-    abi::{WithContractAbi, WithServiceAbi},
-    linera_base_types::AccountOwner,
-    views::{View, RootView},
-    Contract, ContractRuntime, Service, ServiceRuntime,
-};
-use serde_json::json;
-
-pub mod state;
-use crate::state::{
-    BracketMatch, GameReplay, Guild, GuildRole, InstantTacToeState, StakedGame,
-};
-
-use abi::{
-    ChatMessage, GameMode, GameStateResponse, MatchResult, Operation, PowerUpType, Query, Response,
-    RoomInfo, TournamentFormat, TournamentStatus,
-};
-
-pub struct InstantTacToeContract {
-    state: InstantTacToeState,
-    runtime: ContractRuntime<Self>,
-}
-
-impl WithContractAbi for InstantTacToeContract {
-    type Abi = abi::InstantTacToeAbi;
-}
-
-impl Contract for InstantTacToeContract {
-    type Message = ();
-    type InstantiationArgument = ();
-    type Parameters = ();
-    type EventValue = ();
-
-    async fn load(runtime: ContractRuntime<Self>) -> Self {
-        let state = InstantTacToeState::load(runtime.root_view_storage_context())
-            .await
-            .expect("Failed to load contract state");
-        InstantTacToeContract { state, runtime }
-    }
-
-    async fn instantiate(&mut self, _arg: ()) {
-        self.state.next_room_id.set(0);
-        self.state.next_tournament_id.set(0);
-        self.state.next_guild_id.set(0);
-    }
-
-    async fn execute_operation(&mut self, operation: Operation) -> Response {
-        let Some(owner) = self.runtime.authenticated_signer() else {
-            return Response::Error("Action must be authenticated".to_string());
-        };
-
-        match operation {
-            // Basic
-            Operation::SetNickname { name } => self.set_nickname(owner, name).await,
-            Operation::CreateMatch {
-                room_name,
-                password,
-                mode,
-                stake,
-            } => self
-                .create_match(owner, room_name, password, mode, stake)
-                .await,
-            Operation::JoinGame { room_id, password } => self.join_game(owner, room_id, password).await,
-            Operation::MakeMove { room_id, position } => self.make_move(owner, room_id, position).await,
-            Operation::PostMessage { text, room_id } => self.post_message(owner, text, room_id).await,
-            Operation::ResetGame { room_id } => self.reset_game(owner, room_id).await,
-            Operation::LeaveRoom { room_id } => self.leave_room(owner, room_id).await,
-            Operation::Surrender { room_id } => self.surrender(owner, room_id).await,
-
-            // Tournament
-            Operation::CreateTournament {
-                name,
-                format,
-                entry_fee,
-                max_players,
-                prize_distribution,
-            } => self
-                .create_tournament(owner, name, format, entry_fee, max_players, prize_distribution)
-                .await,
-            Operation::JoinTournament { tournament_id } => self.join_tournament(owner, tournament_id).await,
-            Operation::StartTournament { tournament_id } => self.start_tournament(owner, tournament_id).await,
-            Operation::ReportMatchResult {
-                tournament_id,
-                match_id,
-                result,
-            } => self
-                .report_match_result(owner, tournament_id, match_id, result)
-                .await,
-
-            // Economy
-            Operation::DepositTokens { amount } => self.deposit_tokens(owner, amount).await,
-            Operation::WithdrawTokens { amount } => self.withdraw_tokens(owner, amount).await,
-            Operation::ClaimRewards => self.claim_rewards(owner).await,
-
-            // Social
-            Operation::CreateGuild { name, tag } => self.create_guild(owner, name, tag).await,
-            Operation::JoinGuild { guild_id } => self.join_guild(owner, guild_id).await,
-            Operation::InviteToGuild { player, guild_id } => self.invite_to_guild(owner, player, guild_id).await,
-
-            // Advanced
-            Operation::UsePowerUp { room_id, power_up } => self.use_powerup(owner, room_id, power_up).await,
-            Operation::SaveReplay { room_id } => self.save_replay(owner, room_id).await,
-            Operation::ChallengePlayer { player, stake } => self.challenge_player(owner, player, stake).await,
-        }
-    }
-
-    async fn execute_message(&mut self, _message: ()) {
-        // no-op
-    }
-
-    async fn store(mut self) {
-        self.state.save().await.expect("Failed to save state");
-    }
-}
-
-impl InstantTacToeContract {
-    // ===================== CORE GAME =====================
-
-    async fn set_nickname(&mut self, owner: AccountOwner, name: String) -> Response {
-        if name.trim().is_empty() || name.len() > 30 {
-            return Response::Error("Nickname must be 1-30 characters".to_string());
-        }
-        let _ = self.state.nicknames.insert(&owner, name);
-        Response::Ok
-    }
-
-    async fn create_match(
-        &mut self,
-        owner: AccountOwner,
-        room_name: String,
-        password: Option<String>,
-        mode: GameMode,
-        stake: Option<u64>,
-    ) -> Response {
-        if room_name.trim().is_empty() || room_name.len() > 50 {
-            return Response::Error("Room name must be 1-50 characters".to_string());
-        }
-
-        // stake escrow
-        if let Some(amount) = stake {
-            let balance = self
-                .state
-                .player_balances
-                .get(&owner)
-                .await
-                .unwrap()
-                .unwrap_or(0);
-            if balance < amount {
-                return Response::Error("Insufficient balance for stake".to_string());
-            }
-            let _ = self.state.player_balances.insert(&owner, balance - amount);
-            let _ = self.state.escrow_accounts.insert(&owner, amount);
-        }
-
-        let room_id = *self.state.next_room_id.get();
-        let _ = self.state.next_room_id.set(room_id + 1);
-
-        let _ = self.state.room_names.insert(&room_id, room_name);
-        let _ = self.state.room_creators.insert(&room_id, owner);
-        let _ = self.state.room_passwords.insert(&room_id, password);
-        let _ = self.state.room_is_full.insert(&room_id, false);
-        let _ = self.state.room_modes.insert(&room_id, mode);
-        let _ = self.state.room_stakes.insert(&room_id, stake);
-
-        let _ = self.state.game_boards.insert(&room_id, [None; 9]);
-        let _ = self.state.game_current_players.insert(&room_id, 0);
-        let _ = self.state.game_players.insert(&room_id, [Some(owner), None]);
-        let _ = self.state.game_winners.insert(&room_id, None);
-        let _ = self
-            .state
-            .game_move_times
-            .insert(&room_id, self.runtime.system_time().micros());
-        let _ = self.state.chat_counts.insert(&room_id, 0);
-
-        if let Some(amount) = stake {
-            let staked_game = StakedGame {
-                room_id,
-                total_pot: amount * 2,
-                players_stake: [amount, 0],
-                claimed: [false, false],
-            };
-            let _ = self.state.staked_games.insert(&room_id, staked_game);
-        }
-
-        self.post_system_message(room_id, "🛠️ Room created! Waiting for opponent...")
-            .await;
-
-        Response::OkWithData(format!("Room created with ID: {}", room_id))
-    }
-
-    async fn join_game(&mut self, owner: AccountOwner, room_id: u32, password: Option<String>) -> Response {
-        if self.state.room_names.get(&room_id).await.unwrap().is_none() {
-            return Response::Error("Room not found".to_string());
-        }
-
-        let is_full = self
-            .state
-            .room_is_full
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(true);
-        if is_full {
-            return Response::Error("Room is full".to_string());
-        }
-
-        let stored_password = self.state.room_passwords.get(&room_id).await.unwrap();
-        if stored_password.as_ref() != Some(&password) {
-            return Response::Error("Invalid password".to_string());
-        }
-
-        let creator = self.state.room_creators.get(&room_id).await.unwrap();
-        if creator == Some(owner) {
-            return Response::Error("Cannot join your own game".to_string());
-        }
-
-        let stake = self.state.room_stakes.get(&room_id).await.unwrap().flatten();
-        if let Some(amount) = stake {
-            let balance = self
-                .state
-                .player_balances
-                .get(&owner)
-                .await
-                .unwrap()
-                .unwrap_or(0);
-            if balance < amount {
-                return Response::Error("Insufficient balance for stake".to_string());
-            }
-            let _ = self.state.player_balances.insert(&owner, balance - amount);
-            let _ = self.state.escrow_accounts.insert(&owner, amount);
-
-            if let Some(mut staked_game) = self.state.staked_games.get(&room_id).await.unwrap() {
-                staked_game.players_stake[1] = amount;
-                staked_game.total_pot = staked_game.players_stake.iter().sum();
-                let _ = self.state.staked_games.insert(&room_id, staked_game);
-            }
-        }
-
-        let _ = self.state.room_is_full.insert(&room_id, true);
-
-        let Some(mut players) = self.state.game_players.get(&room_id).await.unwrap() else {
-            return Response::Error("Game state missing".to_string());
-        };
-        players[1] = Some(owner);
-        let _ = self.state.game_players.insert(&room_id, players);
-
-        let creator_owner = creator.unwrap_or(owner);
-        let creator_nick = self
-            .state
-            .nicknames
-            .get(&creator_owner)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Anonymous".to_string());
-        let joiner_nick = self
-            .state
-            .nicknames
-            .get(&owner)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Anonymous".to_string());
-
-        let stake_msg = if stake.is_some() { "💰 Staked match! " } else { "" };
-        self.post_system_message(
-            room_id,
-            &format!(
-                "🎮 {}Game started! {} (X) vs {} (O)",
-                stake_msg, creator_nick, joiner_nick
-            ),
-        )
-        .await;
-
-        Response::Ok
-    }
-
-    async fn make_move(&mut self, owner: AccountOwner, room_id: u32, position: u32) -> Response {
-        let Some(mut board) = self.state.game_boards.get(&room_id).await.unwrap() else {
-            return Response::Error("Game not found".to_string());
-        };
-
-        if self
-            .state
-            .game_winners
-            .get(&room_id)
-            .await
-            .unwrap()
-            .flatten()
-            .is_some()
-        {
-            return Response::Error("Game has finished".to_string());
-        }
-
-        let pos = position as usize;
-        if pos >= 9 {
-            return Response::Error("Position must be 0-8".to_string());
-        }
-        if board[pos].is_some() {
-            return Response::Error("Position already taken".to_string());
-        }
-
-        let Some(players) = self.state.game_players.get(&room_id).await.unwrap() else {
-            return Response::Error("Game players missing".to_string());
-        };
-
-        let current_player_idx = self
-            .state
-            .game_current_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(0);
-
-        if players[current_player_idx as usize] != Some(owner) {
-            return Response::Error("Not your turn".to_string());
-        }
-
-        let mode = self
-            .state
-            .room_modes
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(GameMode::Classic);
-
-        if let GameMode::Speed(time_limit) = mode {
-            let last_move = self
-                .state
-                .game_move_times
-                .get(&room_id)
-                .await
-                .unwrap()
-                .unwrap_or(0);
-            let now = self.runtime.system_time().micros();
-            if now - last_move > time_limit * 1_000_000 {
-                return Response::Error("Time limit exceeded".to_string());
-            }
-        }
-
-        let player_char = if current_player_idx == 0 { 'X' } else { 'O' };
-        board[pos] = Some(player_char);
-
-        // move_count precompute
-        let indices = self.state.game_moves_history.indices().await.unwrap_or_default();
-        let move_count = indices
-            .iter()
-            .filter(|(r_id, _)| *r_id == room_id)
-            .count() as u32;
-
-        let _ = self.state.game_moves_history.insert(&(room_id, move_count), position);
-
-        let _ = self.state.game_boards.insert(&room_id, board);
-        let _ = self
-            .state
-            .game_move_times
-            .insert(&room_id, self.runtime.system_time().micros());
-
-        // winner?
-        let Some(board_now) = self.state.game_boards.get(&room_id).await.unwrap() else {
-            return Response::Error("Game state missing".to_string());
-        };
-
-        if let Some(winner) = Self::check_winner(&board_now) {
-            self.handle_game_end(room_id, winner).await;
-            return Response::GameState(self.get_game_state_response(room_id).await);
-        }
-
-        if board_now.iter().all(Option::is_some) {
-            self.handle_game_end(room_id, 'T').await;
-            return Response::GameState(self.get_game_state_response(room_id).await);
-        }
-
-        // switch turns
-        let next_idx = 1 - current_player_idx;
-        let _ = self.state.game_current_players.insert(&room_id, next_idx);
-
-        let next_name = match players[next_idx as usize] {
-            Some(p) => self
-                .state
-                .nicknames
-                .get(&p)
-                .await
-                .unwrap()
-                .unwrap_or_else(|| "Player".to_string()),
-            None => "Player".to_string(),
-        };
-
-        self.post_system_message(
-            room_id,
-            &format!(
-                "➡️ {}'s turn ({})",
-                next_name,
-                if next_idx == 0 { "X" } else { "O" }
-            ),
-        )
-        .await;
-
-        Response::GameState(self.get_game_state_response(room_id).await)
-    }
-
-    async fn handle_game_end(&mut self, room_id: u32, result: char) {
-        let _ = self.state.game_winners.insert(&room_id, Some(result));
-        let players = self
-            .state
-            .game_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or([None, None]);
-
-        match result {
-            'X' => {
-                if let (Some(winner), Some(loser)) = (players[0], players[1]) {
-                    self.update_player_stats(winner, true, false).await;
-                    self.update_player_stats(loser, false, false).await;
-                    self.update_elo(winner, loser, true).await;
-                }
-            }
-            'O' => {
-                if let (Some(winner), Some(loser)) = (players[1], players[0]) {
-                    self.update_player_stats(winner, true, false).await;
-                    self.update_player_stats(loser, false, false).await;
-                    self.update_elo(winner, loser, true).await;
-                }
-            }
-            'T' => {
-                for p in players.iter().flatten() {
-                    self.update_player_stats(*p, false, true).await;
-                }
-                if let (Some(p1), Some(p2)) = (players[0], players[1]) {
-                    self.update_elo(p1, p2, false).await;
-                }
-            }
-            _ => {}
-        }
-
-        // stake distribution (simple)
-        if let Some(mut staked_game) = self.state.staked_games.get(&room_id).await.unwrap() {
-            match result {
-                'X' => {
-                    if let Some(winner) = players[0] {
-                        let cur = self.state.player_balances.get(&winner).await.unwrap().unwrap_or(0);
-                        let _ = self.state.player_balances.insert(&winner, cur + staked_game.total_pot);
-                        staked_game.claimed[0] = true;
-                    }
-                }
-                'O' => {
-                    if let Some(winner) = players[1] {
-                        let cur = self.state.player_balances.get(&winner).await.unwrap().unwrap_or(0);
-                        let _ = self.state.player_balances.insert(&winner, cur + staked_game.total_pot);
-                        staked_game.claimed[1] = true;
-                    }
-                }
-                'T' => {
-                    let half = staked_game.total_pot / 2;
-                    if let Some(p1) = players[0] {
-                        let cur = self.state.player_balances.get(&p1).await.unwrap().unwrap_or(0);
-                        let _ = self.state.player_balances.insert(&p1, cur + half);
-                    }
-                    if let Some(p2) = players[1] {
-                        let cur = self.state.player_balances.get(&p2).await.unwrap().unwrap_or(0);
-                        let _ = self.state.player_balances.insert(&p2, cur + half);
-                    }
-                }
-                _ => {}
-            }
-            let _ = self.state.staked_games.insert(&room_id, staked_game);
-        }
-
-        let msg = match result {
-            'X' => "Player X wins! 🏆",
-            'O' => "Player O wins! 🏆",
-            'T' => "It's a tie! 🤝",
-            _ => "Game over!",
-        };
-        self.post_system_message(room_id, msg).await;
-    }
-
-    async fn reset_game(&mut self, owner: AccountOwner, room_id: u32) -> Response {
-        if !self.is_player_in_room(owner, room_id).await {
-            return Response::Error("You're not in this room".to_string());
-        }
-        if self.state.room_names.get(&room_id).await.unwrap().is_none() {
-            return Response::Error("Room not found".to_string());
-        }
-
-        let _ = self.state.game_boards.insert(&room_id, [None; 9]);
-        let _ = self.state.game_current_players.insert(&room_id, 0);
-        let _ = self.state.game_winners.insert(&room_id, None);
-        let _ = self
-            .state
-            .game_move_times
-            .insert(&room_id, self.runtime.system_time().micros());
-
-        // clear history
-        let indices: Vec<(u32, u32)> = self
-            .state
-            .game_moves_history
-            .indices()
-            .await
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|(r_id, _)| *r_id == room_id)
-            .collect();
-
-        for (rid, move_num) in indices {
-            let _ = self.state.game_moves_history.remove(&(rid, move_num));
-        }
-
-        self.post_system_message(room_id, "🔄 Board reset! First player's turn.")
-            .await;
-        Response::Ok
-    }
-
-    async fn leave_room(&mut self, owner: AccountOwner, room_id: u32) -> Response {
-        if self.state.room_names.get(&room_id).await.unwrap().is_none() {
-            return Response::Error("Room not found".to_string());
-        }
-
-        let Some(mut players) = self.state.game_players.get(&room_id).await.unwrap() else {
-            return Response::Error("Game not found".to_string());
-        };
-
-        let player_nickname = self
-            .state
-            .nicknames
-            .get(&owner)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Anonymous".to_string());
-
-        let mut removed = false;
-        for p in players.iter_mut() {
-            if *p == Some(owner) {
-                *p = None;
-                removed = true;
-            }
-        }
-        if !removed {
-            return Response::Error("You're not in this room".to_string());
-        }
-
-        let _ = self.state.game_players.insert(&room_id, players);
-
-        if players.iter().all(Option::is_none) {
-            let _ = self.state.room_is_full.insert(&room_id, false);
-        }
-
-        self.post_system_message(room_id, &format!("👋 {} left the game", player_nickname))
-            .await;
-        Response::Ok
-    }
-
-    async fn surrender(&mut self, owner: AccountOwner, room_id: u32) -> Response {
-        if !self.is_player_in_room(owner, room_id).await {
-            return Response::Error("You're not in this game".to_string());
-        }
-
-        let Some(players) = self.state.game_players.get(&room_id).await.unwrap() else {
-            return Response::Error("Game not found".to_string());
-        };
-
-        let winner_idx = if players[0] == Some(owner) { 1 } else { 0 };
-        let winner_char = if winner_idx == 0 { 'X' } else { 'O' };
-
-        let _ = self.state.game_winners.insert(&room_id, Some(winner_char));
-
-        if let (Some(p0), Some(p1)) = (players[0], players[1]) {
-            if winner_idx == 0 {
-                self.update_player_stats(p0, true, false).await;
-                self.update_player_stats(p1, false, false).await;
-            } else {
-                self.update_player_stats(p1, true, false).await;
-                self.update_player_stats(p0, false, false).await;
-            }
-        }
-
-        let loser_nick = self
-            .state
-            .nicknames
-            .get(&owner)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Anonymous".to_string());
-        self.post_system_message(room_id, &format!("🏳️ {} surrendered!", loser_nick))
-            .await;
-
-        Response::Ok
-    }
-
-    async fn post_message(&mut self, owner: AccountOwner, text: String, room_id: u32) -> Response {
-        if !self.is_player_in_room(owner, room_id).await {
-            return Response::Error("You're not in this room".to_string());
-        }
-
-        let trimmed = text.trim();
-        if trimmed.is_empty() {
-            return Response::Error("Message cannot be empty".to_string());
-        }
-        if trimmed.len() > 500 {
-            return Response::Error("Message too long (max 500 chars)".to_string());
-        }
-
-        let sender = self
-            .state
-            .nicknames
-            .get(&owner)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Anonymous".to_string());
-
-        let current = self.state.chat_counts.get(&room_id).await.unwrap().unwrap_or(0);
-        let msg = ChatMessage {
-            sender,
-            text: trimmed.to_string(),
-            timestamp: self.runtime.system_time().micros(),
-        };
-
-        let _ = self.state.chat_messages.insert(&(room_id, current), msg);
-        let _ = self.state.chat_counts.insert(&room_id, current + 1);
-
-        Response::Ok
-    }
-
-    // ===================== TOURNAMENTS =====================
-
-    async fn create_tournament(
-        &mut self,
-        owner: AccountOwner,
-        name: String,
-        format: TournamentFormat,
-        entry_fee: Option<u64>,
-        max_players: u32,
-        prize_distribution: Vec<u32>,
-    ) -> Response {
-        if name.trim().is_empty() || name.len() > 50 {
-            return Response::Error("Tournament name must be 1-50 characters".to_string());
-        }
-        if max_players < 2 || max_players > 256 {
-            return Response::Error("Tournament must have 2-256 players".to_string());
-        }
-        if prize_distribution.iter().sum::<u32>() != 100 {
-            return Response::Error("Prize distribution must sum to 100%".to_string());
-        }
-
-        if let Some(fee) = entry_fee {
-            let bal = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
-            if bal < fee {
-                return Response::Error("Insufficient balance for entry fee".to_string());
-            }
-            let _ = self.state.player_balances.insert(&owner, bal - fee);
-        }
-
-        let tournament_id = *self.state.next_tournament_id.get();
-        let _ = self.state.next_tournament_id.set(tournament_id + 1);
-
-        let _ = self.state.tournament_names.insert(&tournament_id, name.clone());
-        let _ = self.state.tournament_formats.insert(&tournament_id, format);
-        let _ = self
-            .state
-            .tournament_status
-            .insert(&tournament_id, TournamentStatus::Registration);
-        let _ = self.state.tournament_entry_fees.insert(&tournament_id, entry_fee);
-        let _ = self
-            .state
-            .tournament_players
-            .insert(&tournament_id, vec![owner]);
-        let _ = self
-            .state
-            .tournament_prize_pools
-            .insert(&tournament_id, entry_fee.unwrap_or(0));
-
-        Response::TournamentCreated { id: tournament_id, name }
-    }
-
-    async fn join_tournament(&mut self, owner: AccountOwner, tournament_id: u64) -> Response {
-        let status = self.state.tournament_status.get(&tournament_id).await.unwrap();
-        if status != Some(TournamentStatus::Registration) {
-            return Response::Error("Tournament not accepting registrations".to_string());
-        }
-
-        let Some(mut players) = self.state.tournament_players.get(&tournament_id).await.unwrap() else {
-            return Response::Error("Tournament not found".to_string());
-        };
-
-        if players.contains(&owner) {
-            return Response::Error("Already registered".to_string());
-        }
-
-        let entry_fee = self.state.tournament_entry_fees.get(&tournament_id).await.unwrap().flatten();
-        if let Some(fee) = entry_fee {
-            let bal = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
-            if bal < fee {
-                return Response::Error("Insufficient balance for entry fee".to_string());
-            }
-            let _ = self.state.player_balances.insert(&owner, bal - fee);
-
-            let pool = self.state.tournament_prize_pools.get(&tournament_id).await.unwrap().unwrap_or(0);
-            let _ = self.state.tournament_prize_pools.insert(&tournament_id, pool + fee);
-        }
-
-        players.push(owner);
-        let _ = self.state.tournament_players.insert(&tournament_id, players.clone());
-
-        Response::TournamentJoined {
-            id: tournament_id,
-            position: players.len() as u32,
-        }
-    }
-
-    async fn start_tournament(&mut self, owner: AccountOwner, tournament_id: u64) -> Response {
-        let Some(players) = self.state.tournament_players.get(&tournament_id).await.unwrap() else {
-            return Response::Error("Tournament not found".to_string());
-        };
-
-        if players.first() != Some(&owner) {
-            return Response::Error("Only tournament creator can start".to_string());
-        }
-        if players.len() < 2 {
-            return Response::Error("Need at least 2 players to start".to_string());
-        }
-
-        let Some(format) = self.state.tournament_formats.get(&tournament_id).await.unwrap() else {
-            return Response::Error("Tournament format missing".to_string());
-        };
-
-        let bracket = match format {
-            TournamentFormat::SingleElimination => self.generate_single_elimination_bracket(&players).await,
-            TournamentFormat::Swiss(rounds) => self.generate_swiss_pairings(&players, rounds).await,
-            TournamentFormat::RoundRobin => self.generate_round_robin_bracket(&players).await,
-        };
-
-        let _ = self.state.tournament_brackets.insert(&tournament_id, bracket);
-        let _ = self
-            .state
-            .tournament_status
-            .insert(&tournament_id, TournamentStatus::InProgress);
-
-        Response::Ok
-    }
-
-    async fn report_match_result(
-        &mut self,
-        owner: AccountOwner,
-        tournament_id: u64,
-        match_id: u64,
-        result: MatchResult,
-    ) -> Response {
-        let Some(mut bracket) = self.state.tournament_brackets.get(&tournament_id).await.unwrap() else {
-            return Response::Error("Tournament bracket not found".to_string());
-        };
-
-        let Some(idx) = bracket.iter().position(|m| m.match_id == match_id) else {
-            return Response::Error("Match not found".to_string());
-        };
-
-        let is_p1 = bracket[idx].player1 == Some(owner);
-        let is_p2 = bracket[idx].player2 == Some(owner);
-        if !is_p1 && !is_p2 {
-            return Response::Error("Not authorized to report this match".to_string());
-        }
-
-        bracket[idx].result = Some(result);
-
-        let _ = self.state.tournament_brackets.insert(&tournament_id, bracket);
-
-        self.check_tournament_completion(tournament_id).await;
-        Response::Ok
-    }
-
-    // ===================== ECONOMY =====================
-
-    async fn deposit_tokens(&mut self, owner: AccountOwner, amount: u64) -> Response {
-        let cur = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
-        let _ = self.state.player_balances.insert(&owner, cur + amount);
-        Response::OkWithData(format!("Deposited {} tokens", amount))
-    }
-
-    async fn withdraw_tokens(&mut self, owner: AccountOwner, amount: u64) -> Response {
-        let cur = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
-        if cur < amount {
-            return Response::Error("Insufficient balance".to_string());
-        }
-        let _ = self.state.player_balances.insert(&owner, cur - amount);
-        Response::OkWithData(format!("Withdrawn {} tokens", amount))
-    }
-
-    async fn claim_rewards(&mut self, owner: AccountOwner) -> Response {
-        // simplified claim: if player is in winners list, give fixed shares
-        let mut total_claimed = 0u64;
-        let tournament_ids: Vec<u64> = self.state.tournament_names.indices().await.unwrap_or_default();
-
-        for tid in tournament_ids {
-            let Some(winners) = self.state.tournament_winners.get(&tid).await.unwrap() else { continue; };
-            if !winners.contains(&owner) {
-                continue;
-            }
-            let pool = self.state.tournament_prize_pools.get(&tid).await.unwrap().unwrap_or(0);
-            let pos = winners.iter().position(|w| w == &owner).unwrap_or(999);
-            let share = match pos {
-                0 => pool * 50 / 100,
-                1 => pool * 30 / 100,
-                2 => pool * 20 / 100,
-                _ => 0,
-            };
-            if share > 0 {
-                let cur = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
-                let _ = self.state.player_balances.insert(&owner, cur + share);
-                total_claimed += share;
-            }
-        }
-
-        if total_claimed > 0 {
-            Response::OkWithData(format!("Claimed {} LIN in rewards", total_claimed))
-        } else {
-            Response::Error("No rewards to claim".to_string())
-        }
-    }
-
-    // ===================== GUILDS =====================
-
-    async fn create_guild(&mut self, owner: AccountOwner, name: String, tag: String) -> Response {
-        if name.trim().is_empty() || name.len() > 30 {
-            return Response::Error("Guild name must be 1-30 characters".to_string());
-        }
-        if tag.trim().is_empty() || tag.len() > 5 {
-            return Response::Error("Guild tag must be 1-5 characters".to_string());
-        }
-
-        let guild_ids: Vec<u64> = self.state.guilds.indices().await.unwrap_or_default();
-        for gid in guild_ids {
-            if let Some(g) = self.state.guilds.get(&gid).await.unwrap() {
-                if g.tag == tag {
-                    return Response::Error("Guild tag already taken".to_string());
-                }
-            }
-        }
-
-        let guild_id = *self.state.next_guild_id.get();
-        let _ = self.state.next_guild_id.set(guild_id + 1);
-
-        let guild = Guild {
-            id: guild_id,
-            name: name.clone(),
-            tag: tag.clone(),
-            level: 1,
-            experience: 0,
-            treasury: 0,
-            member_count: 1,
-            created_at: self.runtime.system_time().micros(),
-        };
-
-        let _ = self.state.guilds.insert(&guild_id, guild);
-        let _ = self
-            .state
-            .guild_members
-            .insert(&(guild_id, owner), GuildRole::Leader);
-        let _ = self.state.player_guilds.insert(&owner, guild_id);
-
-        Response::OkWithData(format!("Guild {} [{}] created!", name, tag))
-    }
-
-    async fn join_guild(&mut self, owner: AccountOwner, guild_id: u64) -> Response {
-        if self.state.player_guilds.get(&owner).await.unwrap().is_some() {
-            return Response::Error("Already in a guild".to_string());
-        }
-
-        let Some(mut guild) = self.state.guilds.get(&guild_id).await.unwrap() else {
-            return Response::Error("Guild not found".to_string());
-        };
-
-        let _ = self
-            .state
-            .guild_members
-            .insert(&(guild_id, owner), GuildRole::Member);
-        let _ = self.state.player_guilds.insert(&owner, guild_id);
-
-        guild.member_count += 1;
-        let _ = self.state.guilds.insert(&guild_id, guild);
-
-        Response::Ok
-    }
-
-    async fn invite_to_guild(&mut self, inviter: AccountOwner, player: AccountOwner, guild_id: u64) -> Response {
-        let Some(role) = self.state.guild_members.get(&(guild_id, inviter)).await.unwrap() else {
-            return Response::Error("You're not in this guild".to_string());
-        };
-
-        match role {
-            GuildRole::Leader | GuildRole::Officer => {}
-            _ => return Response::Error("Insufficient permissions to invite".to_string()),
-        }
-
-        if self.state.player_guilds.get(&player).await.unwrap().is_some() {
-            return Response::Error("Player is already in a guild".to_string());
-        }
-
-        self.join_guild(player, guild_id).await
-    }
-
-    // ===================== ADVANCED =====================
-
-    async fn use_powerup(&mut self, owner: AccountOwner, room_id: u32, power_up: PowerUpType) -> Response {
-        if !self.is_player_in_room(owner, room_id).await {
-            return Response::Error("You're not in this game".to_string());
-        }
-
-        let mode = self
-            .state
-            .room_modes
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(GameMode::Classic);
-
-        if !matches!(mode, GameMode::PowerUp) {
-            return Response::Error("Power-ups not enabled in this game mode".to_string());
-        }
-
-        let powerup_count = self
-            .state
-            .player_powerups
-            .get(&(owner, power_up.clone()))
-            .await
-            .unwrap()
-            .unwrap_or(0);
-
-        if powerup_count == 0 {
-            return Response::Error("You don't have this power-up".to_string());
-        }
-
-        // minimal effect for compile; keep swap/bomb like you had
-        let Some(mut board) = self.state.game_boards.get(&room_id).await.unwrap() else {
-            return Response::Error("Game not found".to_string());
-        };
-
-        let players = self
-            .state
-            .game_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or([None, None]);
-        let cur_idx = self
-            .state
-            .game_current_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(0);
-
-        if players[cur_idx as usize] != Some(owner) {
-            return Response::Error("Not your turn".to_string());
-        }
-
-        match power_up {
-            PowerUpType::DoubleMove => {
-                return Response::Error("Double move not yet implemented".to_string());
-            }
-            PowerUpType::Block => {
-                return Response::Error("Block not yet implemented".to_string());
-            }
-            PowerUpType::Swap => {
-                let me = if cur_idx == 0 { 'X' } else { 'O' };
-                let opp = if cur_idx == 0 { 'O' } else { 'X' };
-                for cell in board.iter_mut() {
-                    if *cell == Some(me) {
-                        *cell = Some(opp);
-                    } else if *cell == Some(opp) {
-                        *cell = Some(me);
-                    }
-                }
-                let _ = self.state.game_boards.insert(&room_id, board);
-            }
-            PowerUpType::Bomb => {
-                // simple clear center area in 3x3 (full board) -> just clear all for now
-                for cell in board.iter_mut() {
-                    *cell = None;
-                }
-                let _ = self.state.game_boards.insert(&room_id, board);
-            }
-        }
-
-        let _ = self
-            .state
-            .player_powerups
-            .insert(&(owner, power_up), powerup_count - 1);
-
-        self.post_system_message(room_id, "⚡ Power-up used").await;
-        Response::Ok
-    }
-
-    async fn save_replay(&mut self, owner: AccountOwner, room_id: u32) -> Response {
-        if !self.is_player_in_room(owner, room_id).await {
-            return Response::Error("You're not in this game".to_string());
-        }
-
-        if self
-            .state
-            .game_winners
-            .get(&room_id)
-            .await
-            .unwrap()
-            .flatten()
-            .is_none()
-        {
-            return Response::Error("Game must be finished to save replay".to_string());
-        }
-
-        let board = self.state.game_boards.get(&room_id).await.unwrap().unwrap_or([None; 9]);
-        let players = self
-            .state
-            .game_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or([None, None]);
-        let winner = self.state.game_winners.get(&room_id).await.unwrap().flatten();
-
-        let indices: Vec<(u32, u32)> = self
-            .state
-            .game_moves_history
-            .indices()
-            .await
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|(r_id, _)| *r_id == room_id)
-            .collect();
-
-        let mut moves = Vec::new();
-        for (rid, move_num) in indices {
-            if let Some(pos) = self.state.game_moves_history.get(&(rid, move_num)).await.unwrap() {
-                moves.push(pos);
-            }
-        }
-
-        let start_time = self
-            .state
-            .game_move_times
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(0);
-        let end_time = self.runtime.system_time().micros();
-        let duration = end_time.saturating_sub(start_time);
-
-        let replay_id = self.state.game_replays.indices().await.unwrap_or_default().len() as u64 + 1;
-        let replay = GameReplay {
-            room_id,
-            moves,
-            players,
-            winner,
-            duration,
-            timestamp: end_time,
-        };
-
-        let _ = self.state.game_replays.insert(&replay_id, replay);
-
-        // avoid unused var warning: board
-        let _ = board;
-
-        Response::OkWithData(format!("Replay saved with ID: {}", replay_id))
-    }
-
-    async fn challenge_player(&mut self, challenger: AccountOwner, player: AccountOwner, stake: Option<u64>) -> Response {
-        if challenger == player {
-            return Response::Error("Cannot challenge yourself".to_string());
-        }
-
-        let player_exists = self.state.player_stats.get(&player).await.unwrap().is_some();
-        if !player_exists {
-            return Response::Error("Player not found".to_string());
-        }
-
-        let challenger_name = self
-            .state
-            .nicknames
-            .get(&challenger)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Player1".to_string());
-        let player_name = self
-            .state
-            .nicknames
-            .get(&player)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| "Player2".to_string());
-
-        let room_name = format!("Challenge: {} vs {}", challenger_name, player_name);
-
-        self.create_match(challenger, room_name, None, GameMode::Classic, stake).await
-    }
-
-    // ===================== HELPERS =====================
-
-    async fn is_player_in_room(&self, owner: AccountOwner, room_id: u32) -> bool {
-        let Some(players) = self.state.game_players.get(&room_id).await.unwrap() else {
-            return false;
-        };
-        players.contains(&Some(owner))
-    }
-
-    async fn update_player_stats(&mut self, player: AccountOwner, is_win: bool, is_draw: bool) {
-        let (mut wins, mut losses, mut draws) = self
-            .state
-            .player_stats
-            .get(&player)
-            .await
-            .unwrap()
-            .unwrap_or((0, 0, 0));
-
-        if is_win {
-            wins += 1;
-            let cur = self.state.player_streaks.get(&player).await.unwrap().unwrap_or(0);
-            let new_streak = if cur > 0 { cur + 1 } else { 1 };
-            let _ = self.state.player_streaks.insert(&player, new_streak);
-        } else if is_draw {
-            draws += 1;
-        } else {
-            losses += 1;
-            let _ = self.state.player_streaks.insert(&player, 0);
-        }
-
-        let _ = self.state.player_stats.insert(&player, (wins, losses, draws));
-    }
-
-    async fn update_elo(&mut self, p1: AccountOwner, p2: AccountOwner, p1_won: bool) {
-        let elo1 = self.state.player_elo.get(&p1).await.unwrap().unwrap_or(1500);
-        let elo2 = self.state.player_elo.get(&p2).await.unwrap().unwrap_or(1500);
-
-        let k = 32.0;
-        let expected1 = 1.0 / (1.0 + 10.0_f64.powf((elo2 as f64 - elo1 as f64) / 400.0));
-        let actual1 = if p1_won { 1.0 } else { 0.0 };
-
-        let new1 = (elo1 as f64 + k * (actual1 - expected1)).round().max(0.0) as u32;
-        let new2 = (elo2 as f64 + k * ((1.0 - actual1) - (1.0 - expected1))).round().max(0.0) as u32;
-
-        let _ = self.state.player_elo.insert(&p1, new1);
-        let _ = self.state.player_elo.insert(&p2, new2);
-    }
-
-    async fn post_system_message(&mut self, room_id: u32, text: &str) {
-        let cur = self.state.chat_counts.get(&room_id).await.unwrap().unwrap_or(0);
-        let msg = ChatMessage {
-            sender: "🛠️ SYSTEM".to_string(),
-            text: text.to_string(),
-            timestamp: self.runtime.system_time().micros(),
-        };
-        let _ = self.state.chat_messages.insert(&(room_id, cur), msg);
-        let _ = self.state.chat_counts.insert(&room_id, cur + 1);
-    }
-
-    async fn get_game_state_response(&self, room_id: u32) -> GameStateResponse {
-        let board = self.state.game_boards.get(&room_id).await.unwrap().unwrap_or([None; 9]);
-        let players = self
-            .state
-            .game_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or([None, None]);
-        let current_idx = self
-            .state
-            .game_current_players
-            .get(&room_id)
-            .await
-            .unwrap()
-            .unwrap_or(0);
-        let winner = self.state.game_winners.get(&room_id).await.unwrap().flatten();
-
-        let indices = self.state.game_moves_history.indices().await.unwrap_or_default();
-        let mut moves_history = Vec::new();
-        for (rid, move_num) in indices {
-            if rid != room_id {
-                continue;
-            }
-            if let Some(pos) = self.state.game_moves_history.get(&(rid, move_num)).await.unwrap() {
-                moves_history.push(pos);
-            }
-        }
-
-        let stats0 = match players[0] {
-            Some(p) => self.state.player_stats.get(&p).await.unwrap().unwrap_or((0, 0, 0)),
-            None => (0, 0, 0),
-        };
-        let stats1 = match players[1] {
-            Some(p) => self.state.player_stats.get(&p).await.unwrap().unwrap_or((0, 0, 0)),
-            None => (0, 0, 0),
-        };
-
-        GameStateResponse {
-            board,
-            players,
-            current_player: players[current_idx as usize],
-            winner,
-            time_remaining: None,
-            player_stats: [stats0, stats1],
-            moves_history,
-        }
-    }
-
-    fn check_winner(board: &[Option<char>; 9]) -> Option<char> {
-        let lines = [
-            [0, 1, 2],
-            [3, 4, 5],
-            [6, 7, 8],
-            [0, 3, 6],
-            [1, 4, 7],
-            [2, 5, 8],
-            [0, 4, 8],
-            [2, 4, 6],
-        ];
-        for [a, b, c] in lines {
-            if board[a].is_some() && board[a] == board[b] && board[a] == board[c] {
-                return board[a];
-            }
-        }
-        None
-    }
-
-    // bracket helpers (type-safe u64 math)
-    async fn generate_single_elimination_bracket(&self, players: &[AccountOwner]) -> Vec<BracketMatch> {
-        let n = players.len() as u64;
-        if n < 2 {
-            return vec![];
-        }
-
-        let mut matches: Vec<BracketMatch> = Vec::new();
-        let mut match_id: u64 = 1;
-
-        // first round
-        let mut i = 0usize;
-        while i < players.len() {
-            let p1 = Some(players[i]);
-            let p2 = if i + 1 < players.len() { Some(players[i + 1]) } else { None };
-
-            matches.push(BracketMatch {
-                match_id,
-                player1: p1,
-                player2: p2,
-                result: None,
-                next_match: None,
-                round: 1,
-            });
-
-            match_id += 1;
-            i += 2;
-        }
-
-        // (simple: no full tree wiring, but compiles clean)
-        let _ = n;
-        matches
-    }
-
-    async fn generate_swiss_pairings(&self, players: &[AccountOwner], rounds: u32) -> Vec<BracketMatch> {
-        let mut out = Vec::new();
-        let mut match_id: u64 = 1;
-
-        for round in 1..=rounds {
-            let mut i = 0usize;
-            while i + 1 < players.len() {
-                out.push(BracketMatch {
-                    match_id,
-                    player1: Some(players[i]),
-                    player2: Some(players[i + 1]),
-                    result: None,
-                    next_match: None,
-                    round,
-                });
-                match_id += 1;
-                i += 2;
-            }
-        }
-        out
-    }
-
-    async fn generate_round_robin_bracket(&self, players: &[AccountOwner]) -> Vec<BracketMatch> {
-        let mut out = Vec::new();
-        let mut match_id: u64 = 1;
-
-        for i in 0..players.len() {
-            for j in (i + 1)..players.len() {
-                out.push(BracketMatch {
-                    match_id,
-                    player1: Some(players[i]),
-                    player2: Some(players[j]),
-                    result: None,
-                    next_match: None,
-                    round: 1,
-                });
-                match_id += 1;
-            }
-        }
-        out
-    }
-
-    async fn check_tournament_completion(&mut self, tournament_id: u64) {
-        let Some(bracket) = self.state.tournament_brackets.get(&tournament_id).await.unwrap() else {
-            return;
-        };
-
-        let all_done = bracket.iter().all(|m| m.result.is_some());
-        if !all_done {
-            return;
-        }
-
-        let players = self
-            .state
-            .tournament_players
-            .get(&tournament_id)
-            .await
-            .unwrap()
-            .unwrap_or_default();
-
-        let winners = players.into_iter().take(3).collect::<Vec<_>>();
-
-        let _ = self.state.tournament_winners.insert(&tournament_id, winners);
-        let _ = self
-            .state
-            .tournament_status
-            .insert(&tournament_id, TournamentStatus::Completed);
-    }
-}
-
-// ===================== SERVICE =====================
-
-pub struct InstantTacToeService {
-    state: InstantTacToeState,
-    runtime: ServiceRuntime<Self>,
-}
-
-impl WithServiceAbi for InstantTacToeService {
-    type Abi = abi::InstantTacToeAbi;
-}
-
-impl Service for InstantTacToeService {
-    type Parameters = ();
-
-    async fn new(runtime: ServiceRuntime<Self>) -> Self {
-        let state = InstantTacToeState::load(runtime.root_view_storage_context())
-            .await
-            .expect("Failed to load service state");
-        InstantTacToeService { state, runtime }
-    }
-
-    async fn handle_query(&self, query: Query) -> GraphQLResponse {
-        match query {
-            Query::GetLobby => self.get_lobby().await,
-            Query::GetBoard { room_id } => self.get_board(room_id).await,
-            Query::GetChat { room_id } => self.get_chat(room_id).await,
-            Query::GetPlayerStats { player } => self.get_player_stats(player).await,
-            Query::GetTournaments { status } => self.get_tournaments(status).await,
-            Query::GetTournamentDetails { tournament_id } => self.get_tournament_details(tournament_id).await,
-            Query::GetPlayerBalance { player } => self.get_player_balance(player).await,
-            Query::GetGuilds => self.get_guilds().await,
-            Query::GetGuildDetails { guild_id } => self.get_guild_details(guild_id).await,
-            Query::GetReplays { player } => self.get_replays(player).await,
-            Query::GetLeaderboard { mode, limit } => self.get_leaderboard(mode, limit).await,
-        }
-    }
-}
-
-impl InstantTacToeService {
-    async fn get_lobby(&self) -> GraphQLResponse {
-        let mut rooms = Vec::new();
-        let room_ids: Vec<u32> = self.state.room_names.indices().await.unwrap_or_default();
-
-        for room_id in room_ids {
-            let Some(name) = self.state.room_names.get(&room_id).await.unwrap() else { continue; };
-            let Some(creator) = self.state.room_creators.get(&room_id).await.unwrap() else { continue; };
-            let is_full = self.state.room_is_full.get(&room_id).await.unwrap().unwrap_or(false);
-            let password = self.state.room_passwords.get(&room_id).await.unwrap().unwrap_or(None);
-            let mode = self.state.room_modes.get(&room_id).await.unwrap().unwrap_or(GameMode::Classic);
-            let stake = self.state.room_stakes.get(&room_id).await.unwrap().flatten();
-
-            let player_count = self
-                .state
-                .game_players
-                .get(&room_id)
-                .await
-                .unwrap()
-                .map(|p| p.iter().filter(|x| x.is_some()).count() as u8)
-                .unwrap_or(0);
-
-            rooms.push(RoomInfo {
-                room_id,
-                name,
-                creator,
-                is_full,
-                has_password: password.is_some(),
-                player_count,
-                mode,
-                stake,
-            });
-        }
-
-        GraphQLResponse::new(Value::from_json(json!(rooms)).unwrap_or_default())
-    }
-
-    async fn get_board(&self, room_id: u32) -> GraphQLResponse {
-        let Some(board) = self.state.game_boards.get(&room_id).await.unwrap() else {
-            return GraphQLResponse::new(Value::from_json(json!({"error":"Game not found"})).unwrap_or_default());
-        };
-
-        let players = self.state.game_players.get(&room_id).await.unwrap().unwrap_or([None, None]);
-        let current_player_idx = self.state.game_current_players.get(&room_id).await.unwrap().unwrap_or(0);
-        let winner = self.state.game_winners.get(&room_id).await.unwrap().flatten();
-        let last_move_time = self.state.game_move_times.get(&room_id).await.unwrap().unwrap_or(0);
-        let current_time = self.runtime.system_time().micros();
-        let mode = self.state.room_modes.get(&room_id).await.unwrap().unwrap_or(GameMode::Classic);
-
-        let player_x_nickname = match players[0] {
-            Some(p) => self.state.nicknames.get(&p).await.unwrap().unwrap_or_else(|| "Player X".to_string()),
-            None => "Player X".to_string(),
-        };
-        let player_o_nickname = match players[1] {
-            Some(p) => self.state.nicknames.get(&p).await.unwrap().unwrap_or_else(|| "Player O".to_string()),
-            None => "Player O".to_string(),
-        };
-
-        // precompute moves_count (NO let blocks inside json!)
-        let indices = self.state.game_moves_history.indices().await.unwrap_or_default();
-        let moves_count = indices.iter().filter(|(r_id, _)| *r_id == room_id).count();
-
-        let response = json!({
-            "board": board,
-            "players": players,
-            "current_player": players.get(current_player_idx as usize).copied().flatten(),
-            "winner": winner,
-            "last_move_time": last_move_time,
-            "current_time": current_time,
-            "is_timed_out": match mode {
-                GameMode::Speed(time_limit) => current_time - last_move_time > time_limit * 1_000_000,
-                _ => false
-            },
-            "player_nicknames": {
-                "player_x": player_x_nickname,
-                "player_o": player_o_nickname
-            },
-            "mode": mode,
-            "stake": self.state.room_stakes.get(&room_id).await.unwrap().flatten(),
-            "moves_count": moves_count
-        });
-
-        GraphQLResponse::new(Value::from_json(response).unwrap_or_default())
-    }
-
-    async fn get_chat(&self, room_id: u32) -> GraphQLResponse {
-        let Some(count) = self.state.chat_counts.get(&room_id).await.unwrap() else {
-            return GraphQLResponse::new(Value::from_json(json!([])).unwrap_or_default());
-        };
-
-        let mut messages = Vec::with_capacity(count as usize);
-        for i in 0..count {
-            if let Some(msg) = self.state.chat_messages.get(&(room_id, i)).await.unwrap() {
-                messages.push(msg);
-            }
-        }
-        GraphQLResponse::new(Value::from_json(json!(messages)).unwrap_or_default())
-    }
-
-    async fn get_player_stats(&self, player: AccountOwner) -> GraphQLResponse {
-        let stats = self.state.player_stats.get(&player).await.unwrap().unwrap_or((0, 0, 0));
-        let elo = self.state.player_elo.get(&player).await.unwrap().unwrap_or(1500);
-        let streak = self.state.player_streaks.get(&player).await.unwrap().unwrap_or(0);
-        let nickname = self.state.nicknames.get(&player).await.unwrap().unwrap_or_else(|| "Anonymous".to_string());
-        let balance = self.state.player_balances.get(&player).await.unwrap().unwrap_or(0);
-
-        let guild = if let Some(guild_id) = self.state.player_guilds.get(&player).await.unwrap() {
-            self.state.guilds.get(&guild_id).await.unwrap()
-        } else {
-            None
-        };
-
-        let total = stats.0 + stats.1 + stats.2;
-        let win_rate = if total > 0 {
-            (stats.0 as f64 / total as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let response = json!({
-            "player": player,
-            "nickname": nickname,
-            "elo": elo,
-            "wins": stats.0,
-            "losses": stats.1,
-            "draws": stats.2,
-            "total_games": total,
-            "win_rate": win_rate,
-            "current_streak": streak,
-            "balance": balance,
-            "guild": guild
-        });
-
-        GraphQLResponse::new(Value::from_json(response).unwrap_or_default())
-    }
-
-    async fn get_tournaments(&self, status: Option<TournamentStatus>) -> GraphQLResponse {
-        let mut tournaments = Vec::new();
-        let tournament_ids: Vec<u64> = self.state.tournament_names.indices().await.unwrap_or_default();
-
-        for tid in tournament_ids {
-            let Some(name) = self.state.tournament_names.get(&tid).await.unwrap() else { continue; };
-            let Some(format) = self.state.tournament_formats.get(&tid).await.unwrap() else { continue; };
-            let Some(ts) = self.state.tournament_status.get(&tid).await.unwrap() else { continue; };
-
-            if let Some(filter) = status.clone() {
-                if ts != filter {
-                    continue;
-                }
-            }
-
-            let entry_fee = self.state.tournament_entry_fees.get(&tid).await.unwrap().flatten();
-            let players = self.state.tournament_players.get(&tid).await.unwrap().unwrap_or_default();
-            let prize_pool = self.state.tournament_prize_pools.get(&tid).await.unwrap().unwrap_or(0);
-
-            tournaments.push(json!({
-                "id": tid,
-                "name": name,
-                "format": format,
-                "status": ts,
-                "entry_fee": entry_fee,
-                "prize_pool": prize_pool,
-                "players": players
-            }));
-        }
-
-        GraphQLResponse::new(Value::from_json(json!(tournaments)).unwrap_or_default())
-    }
-
-    async fn get_tournament_details(&self, tournament_id: u64) -> GraphQLResponse {
-        let Some(name) = self.state.tournament_names.get(&tournament_id).await.unwrap() else {
-            return GraphQLResponse::new(Value::from_json(json!({"error":"Tournament not found"})).unwrap_or_default());
-        };
-
-        let format = self.state.tournament_formats.get(&tournament_id).await.unwrap();
-        let status = self.state.tournament_status.get(&tournament_id).await.unwrap();
-        let entry_fee = self.state.tournament_entry_fees.get(&tournament_id).await.unwrap().flatten();
-        let players = self.state.tournament_players.get(&tournament_id).await.unwrap().unwrap_or_default();
-        let prize_pool = self.state.tournament_prize_pools.get(&tournament_id).await.unwrap().unwrap_or(0);
-        let bracket = self.state.tournament_brackets.get(&tournament_id).await.unwrap();
-        let winners = self.state.tournament_winners.get(&tournament_id).await.unwrap();
-
-        let response = json!({
-            "id": tournament_id,
-            "name": name,
-            "format": format,
-            "status": status,
-            "entry_fee": entry_fee,
-            "prize_pool": prize_pool,
-            "players": players,
-            "bracket": bracket,
-            "winners": winners
-        });
-
-        GraphQLResponse::new(Value::from_json(response).unwrap_or_default())
-    }
-
-    async fn get_player_balance(&self, player: AccountOwner) -> GraphQLResponse {
-        let balance = self.state.player_balances.get(&player).await.unwrap().unwrap_or(0);
-        let in_escrow = self.state.escrow_accounts.get(&player).await.unwrap().unwrap_or(0);
-
-        let response = json!({
-            "player": player,
-            "balance": balance,
-            "in_escrow": in_escrow,
-            "total": balance + in_escrow
-        });
-
-        GraphQLResponse::new(Value::from_json(response).unwrap_or_default())
-    }
-
-    async fn get_guilds(&self) -> GraphQLResponse {
-        let mut guilds = Vec::new();
-        let ids: Vec<u64> = self.state.guilds.indices().await.unwrap_or_default();
-        for gid in ids {
-            if let Some(g) = self.state.guilds.get(&gid).await.unwrap() {
-                guilds.push(g);
-            }
-        }
-        guilds.sort_by(|a, b| b.level.cmp(&a.level).then(b.member_count.cmp(&a.member_count)));
-        GraphQLResponse::new(Value::from_json(json!(guilds)).unwrap_or_default())
-    }
-
-    async fn get_guild_details(&self, guild_id: u64) -> GraphQLResponse {
-        let Some(guild) = self.state.guilds.get(&guild_id).await.unwrap() else {
-            return GraphQLResponse::new(Value::from_json(json!({"error":"Guild not found"})).unwrap_or_default());
-        };
-
-        let member_keys: Vec<(u64, AccountOwner)> = self.state.guild_members.indices().await.unwrap_or_default();
-        let mut members = Vec::new();
-
-        for (gid, member) in member_keys.into_iter().filter(|(gid, _)| *gid == guild_id) {
-            let _ = gid;
-            if let Some(role) = self.state.guild_members.get(&(guild_id, member)).await.unwrap() {
-                let nickname = self.state.nicknames.get(&member).await.unwrap().unwrap_or_else(|| "Anonymous".to_string());
-                let stats = self.state.player_stats.get(&member).await.unwrap().unwrap_or((0, 0, 0));
-                let elo = self.state.player_elo.get(&member).await.unwrap().unwrap_or(1500);
-
-                members.push(json!({
-                    "account": member,
-                    "nickname": nickname,
-                    "role": role,
-                    "wins": stats.0,
-                    "losses": stats.1,
-                    "draws": stats.2,
-                    "elo": elo
-                }));
-            }
-        }
-
-        let total_elo: u64 = members.iter().map(|m| m["elo"].as_u64().unwrap_or(0)).sum();
-        let avg_elo = if members.is_empty() { 0 } else { total_elo / members.len() as u64 };
-
-        let response = json!({
-            "guild": guild,
-            "members": members,
-            "member_count": guild.member_count,
-            "avg_elo": avg_elo
-        });
-
-        GraphQLResponse::new(Value::from_json(response).unwrap_or_default())
-    }
-
-    async fn get_replays(&self, player: AccountOwner) -> GraphQLResponse {
-        let mut replays = Vec::new();
-        let ids: Vec<u64> = self.state.game_replays.indices().await.unwrap_or_default();
-
-        for rid in ids {
-            if let Some(r) = self.state.game_replays.get(&rid).await.unwrap() {
-                if r.players.contains(&Some(player)) {
-                    replays.push(json!({
-                        "id": rid,
-                        "room_id": r.room_id,
-                        "players": r.players,
-                        "winner": r.winner,
-                        "duration": r.duration,
-                        "timestamp": r.timestamp,
-                        "move_count": r.moves.len()
-                    }));
-                }
-            }
-        }
-
-        replays.sort_by(|a, b| b["timestamp"].as_u64().unwrap_or(0).cmp(&a["timestamp"].as_u64().unwrap_or(0)));
-        GraphQLResponse::new(Value::from_json(json!(replays)).unwrap_or_default())
-    }
-
-    async fn get_leaderboard(&self, mode: GameMode, limit: u32) -> GraphQLResponse {
-        let mut players = Vec::new();
-        let accounts: Vec<AccountOwner> = self.state.player_stats.indices().await.unwrap_or_default();
-
-        for p in accounts {
-            let stats = self.state.player_stats.get(&p).await.unwrap().unwrap_or((0, 0, 0));
-            let elo = self.state.player_elo.get(&p).await.unwrap().unwrap_or(1500);
-            let nickname = self.state.nicknames.get(&p).await.unwrap().unwrap_or_else(|| "Anonymous".to_string());
-
-            let total = stats.0 + stats.1 + stats.2;
-            let win_rate = if total > 0 { (stats.0 as f64 / total as f64) * 100.0 } else { 0.0 };
-
-            players.push(json!({
-                "account": p,
-                "nickname": nickname,
-                "elo": elo,
-                "wins": stats.0,
-                "losses": stats.1,
-                "draws": stats.2,
-                "total_games": total,
-                "win_rate": win_rate
-            }));
-        }
-
-        players.sort_by(|a, b| b["elo"].as_u64().unwrap_or(0).cmp(&a["elo"].as_u64().unwrap_or(0)));
-        let limited = players.into_iter().take(limit as usize).collect::<Vec<_>>();
-
-        GraphQLResponse::new(Value::from_json(json!({"mode": mode, "players": limited})).unwrap_or_default())
-    }
-}
+use std::sync::Arc;
+
+use async_graphql::{EmptySubscription, Request, Response as GraphQLResponse, Schema};
+use linera_sdk::{
+    abi::{WithContractAbi, WithServiceAbi},
+    linera_base_types::{Account, AccountOwner, Amount},
+    views::{View, RootView},
+    Contract, ContractRuntime, Service, ServiceRuntime,
+};
+
+pub mod schema;
+pub mod state;
+use crate::schema::{MutationRoot, QueryRoot};
+use crate::state::{
+    BracketMatch, GameReplay, Guild, GuildRole, InstantTacToeState, MatchSeries, Room, StakedGame,
+    Transaction, TransactionKind, STORAGE_VERSION,
+};
+
+use abi::{
+    error::{GameError, OfferKind},
+    events::{self, GameEvent},
+    rules::{self, Board, ChessClock, GameState},
+    solver, ChatMessage, Difficulty, GameConfig, GameMode, GameStateResponse, MatchResult, Operation, PowerUpType,
+    Response, RoomStatus, TournamentFormat, TournamentStatus,
+};
+
+/// Prize split of tournaments created before the creator's distribution was stored.
+const LEGACY_PRIZE_DISTRIBUTION: [u32; 3] = [50, 30, 20];
+
+/// Finishers recorded as winners of a tournament, and so the most places a
+/// prize distribution can pay.
+const PAID_PLACES: usize = 3;
+
+/// First-release entries `migrate_storage` moves per operation.
+const MIGRATION_BATCH: usize = 100;
+
+/// How games in a new room are played.
+struct MatchSettings {
+    mode: GameMode,
+    increment: Option<u64>, // Speed only, seconds added after each move
+    series_length: Option<u32>,
+}
+
+pub struct InstantTacToeContract {
+    state: InstantTacToeState,
+    runtime: ContractRuntime<Self>,
+}
+
+impl WithContractAbi for InstantTacToeContract {
+    type Abi = abi::InstantTacToeAbi;
+}
+
+impl Contract for InstantTacToeContract {
+    type Message = ();
+    type InstantiationArgument = GameConfig;
+    type Parameters = ();
+    type EventValue = GameEvent;
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = InstantTacToeState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load contract state");
+        InstantTacToeContract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, config: GameConfig) {
+        self.state.config.set(config);
+        self.state.storage_version.set(STORAGE_VERSION);
+        self.state.next_room_id.set(0);
+        self.state.next_tournament_id.set(0);
+        self.state.next_guild_id.set(0);
+        self.state.next_replay_id.set(1);
+    }
+
+    async fn execute_operation(&mut self, operation: Operation) -> Response {
+        if !self.migrate_storage().await {
+            return Response::Error(GameError::MigrationInProgress);
+        }
+
+        let Some(owner) = self.runtime.authenticated_signer() else {
+            return Response::Error(GameError::NotAuthenticated);
+        };
+
+        match operation {
+            // Basic
+            Operation::SetNickname { name } => self.set_nickname(owner, name).await,
+            Operation::CreateMatch {
+                room_name,
+                password,
+                mode,
+                stake,
+                increment,
+                series_length,
+            } => {
+                let settings = MatchSettings {
+                    mode,
+                    increment,
+                    series_length,
+                };
+                self.create_match(owner, room_name, password, stake, settings).await
+            }
+            Operation::CreateBotMatch { difficulty } => self.create_bot_match(owner, difficulty).await,
+            Operation::JoinGame { room_id, password } => self.join_game(owner, room_id, password).await,
+            Operation::MakeMove { room_id, position } => self.make_move(owner, room_id, position, false).await,
+            Operation::MakeUltimateMove { room_id, board, cell } => {
+                if board >= 9 || cell >= 9 {
+                    return Response::Error(GameError::CellOutOfRange);
+                }
+                self.make_move(owner, room_id, board * 9 + cell, true).await
+            }
+            Operation::PostMessage { text, room_id } => self.post_message(owner, text, room_id).await,
+            Operation::OfferRematch { room_id } => self.offer_rematch(owner, room_id).await,
+            Operation::AcceptRematch { room_id } => self.accept_rematch(owner, room_id).await,
+            Operation::LeaveRoom { room_id } => self.leave_room(owner, room_id).await,
+            Operation::CancelRoom { room_id } => self.cancel_room(owner, room_id).await,
+            Operation::Surrender { room_id } => self.surrender(owner, room_id).await,
+            Operation::ClaimTimeout { room_id } => self.claim_timeout(owner, room_id).await,
+            Operation::OfferDraw { room_id } => self.offer_draw(owner, room_id).await,
+            Operation::AcceptDraw { room_id } => self.accept_draw(owner, room_id).await,
+            Operation::RequestTakeback { room_id } => self.request_takeback(owner, room_id).await,
+            Operation::AcceptTakeback { room_id } => self.accept_takeback(owner, room_id).await,
+
+            // Tournament
+            Operation::CreateTournament {
+                name,
+                format,
+                entry_fee,
+                max_players,
+                prize_distribution,
+            } => self
+                .create_tournament(owner, name, format, entry_fee, max_players, prize_distribution)
+                .await,
+            Operation::JoinTournament { tournament_id } => self.join_tournament(owner, tournament_id).await,
+            Operation::StartTournament { tournament_id } => self.start_tournament(owner, tournament_id).await,
+            Operation::ReportMatchResult {
+                tournament_id,
+                match_id,
+                result,
+            } => self
+                .report_match_result(owner, tournament_id, match_id, result)
+                .await,
+
+            // Economy
+            Operation::DepositTokens { amount } => self.deposit_tokens(owner, amount).await,
+            Operation::WithdrawTokens { amount } => self.withdraw_tokens(owner, amount).await,
+            Operation::ClaimRewards => self.claim_rewards(owner).await,
+
+            // Social
+            Operation::CreateGuild { name, tag } => self.create_guild(owner, name, tag).await,
+            Operation::JoinGuild { guild_id } => self.join_guild(owner, guild_id).await,
+            Operation::InviteToGuild { player, guild_id } => self.invite_to_guild(owner, player, guild_id).await,
+
+            // Advanced
+            Operation::UsePowerUp { room_id, power_up } => self.use_powerup(owner, room_id, power_up).await,
+            Operation::SaveReplay { room_id } => self.save_replay(owner, room_id).await,
+            Operation::ChallengePlayer { player, stake } => self.challenge_player(owner, player, stake).await,
+        }
+    }
+
+    async fn execute_message(&mut self, _message: ()) {
+        // no-op
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl InstantTacToeContract {
+    // ===================== CORE GAME =====================
+
+    async fn set_nickname(&mut self, owner: AccountOwner, name: String) -> Response {
+        if name.trim().is_empty() || name.len() > 30 {
+            return Response::Error(GameError::invalid_length("nickname", 1, 30));
+        }
+        let _ = self.state.nicknames.insert(&owner, name);
+        Response::Ok
+    }
+
+    async fn create_match(
+        &mut self,
+        owner: AccountOwner,
+        room_name: String,
+        password: Option<String>,
+        stake: Option<u64>,
+        settings: MatchSettings,
+    ) -> Response {
+        if room_name.trim().is_empty() || room_name.len() > 50 {
+            return Response::Error(GameError::invalid_length("room_name", 1, 50));
+        }
+
+        let MatchSettings {
+            mode,
+            increment,
+            series_length,
+        } = settings;
+
+        let clock = match mode {
+            GameMode::Speed(base) if base == 0 || base > rules::MAX_TIME_BANK_SECS => {
+                return Response::Error(GameError::InvalidTimeBank);
+            }
+            GameMode::Speed(_) if increment.is_some_and(|secs| secs > rules::MAX_INCREMENT_SECS) => {
+                return Response::Error(GameError::InvalidIncrement);
+            }
+            GameMode::Speed(base) => Some(ChessClock::new(base, increment.unwrap_or(0))),
+            _ if increment.is_some() => {
+                return Response::Error(GameError::IncrementRequiresSpeed);
+            }
+            _ => None,
+        };
+
+        if let Some(n) = series_length {
+            if n == 0 || n % 2 == 0 || n > 15 {
+                return Response::Error(GameError::InvalidSeriesLength { length: n });
+            }
+        }
+
+        // the stake is escrowed once the room exists
+        if let Some(amount) = stake {
+            let balance = self
+                .state
+                .player_balances
+                .get(&owner)
+                .await
+                .unwrap()
+                .unwrap_or(0);
+            if balance < amount {
+                return Response::Error(GameError::InsufficientBalance {
+                    required: amount,
+                    available: balance,
+                });
+            }
+        }
+
+        let room_id = *self.state.next_room_id.get();
+        let _ = self.state.next_room_id.set(room_id + 1);
+
+        let now = self.runtime.system_time().micros();
+        let mut room = Room::new(room_name, owner, password, mode, stake, now);
+        room.clock = clock;
+        room.series = series_length.map(|n| MatchSeries::new(n, room.players));
+        self.save_room(room_id, &room).await;
+
+        if let Some(amount) = stake {
+            self.escrow_stake(owner, room_id, amount).await;
+            let staked_game = StakedGame {
+                room_id,
+                total_pot: amount,
+                players_stake: [amount, 0],
+                claimed: [false, false],
+            };
+            let _ = self.state.staked_games.insert(&room_id, staked_game);
+        }
+
+        let event = GameEvent::PlayerJoined {
+            room_id,
+            player: owner,
+            seat: 0,
+        };
+        self.emit_room_event(room_id, &event).await;
+        self.emit_lobby_event(&event).await;
+        self.post_system_message(room_id, "🛠️ Room created! Waiting for opponent...")
+            .await;
+
+        Response::OkWithData(format!("Room created with ID: {}", room_id))
+    }
+
+    async fn create_bot_match(&mut self, owner: AccountOwner, difficulty: Difficulty) -> Response {
+        let bot = self.bot_owner();
+        let room_id = *self.state.next_room_id.get();
+        let _ = self.state.next_room_id.set(room_id + 1);
+
+        let now = self.runtime.system_time().micros();
+        let name = format!("Practice vs {} bot", difficulty);
+        let mut room = Room::new(name, owner, None, GameMode::Classic, None, now);
+        room.bot = Some(difficulty);
+        // the bot takes the second seat right away
+        room.players[1] = Some(bot);
+        if let Err(e) = room.transition(RoomStatus::InProgress) {
+            return Response::Error(e);
+        }
+        self.save_room(room_id, &room).await;
+        let _ = self.state.nicknames.insert(&bot, "🤖 Bot".to_string());
+        self.emit_room_event(
+            room_id,
+            &GameEvent::PlayerJoined {
+                room_id,
+                player: owner,
+                seat: 0,
+            },
+        )
+        .await;
+
+        let nickname = self.nickname(owner).await;
+        self.post_system_message(
+            room_id,
+            &format!("🤖 Practice game started! {} (X) vs {} bot (O)", nickname, difficulty),
+        )
+        .await;
+
+        Response::OkWithData(format!("Room created with ID: {}", room_id))
+    }
+
+    async fn join_game(&mut self, owner: AccountOwner, room_id: u32, password: Option<String>) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if let Err(e) = room.require_status(&[RoomStatus::WaitingForOpponent]) {
+            return Response::Error(e);
+        }
+
+        if room.password != password {
+            return Response::Error(GameError::InvalidPassword);
+        }
+
+        if room.creator == owner {
+            return Response::Error(GameError::CannotJoinOwnRoom);
+        }
+
+        let stake = room.stake;
+        if let Some(amount) = stake {
+            let balance = self
+                .state
+                .player_balances
+                .get(&owner)
+                .await
+                .unwrap()
+                .unwrap_or(0);
+            if balance < amount {
+                return Response::Error(GameError::InsufficientBalance {
+                    required: amount,
+                    available: balance,
+                });
+            }
+            self.escrow_stake(owner, room_id, amount).await;
+
+            if let Some(mut staked_game) = self.state.staked_games.get(&room_id).await.unwrap() {
+                staked_game.players_stake[1] = amount;
+                staked_game.total_pot = staked_game.players_stake.iter().sum();
+                let _ = self.state.staked_games.insert(&room_id, staked_game);
+            }
+        }
+
+        if let Err(e) = room.transition(RoomStatus::InProgress) {
+            return Response::Error(e);
+        }
+
+        room.players[1] = Some(owner);
+        if let Some(series) = room.series.as_mut() {
+            series.players = room.players;
+        }
+
+        let now = self.runtime.system_time().micros();
+        room.last_move_at = now;
+        room.started_at = now;
+        if let Some(clock) = room.clock.as_mut() {
+            clock.start(0, now);
+        }
+        self.save_room(room_id, &room).await;
+
+        let event = GameEvent::PlayerJoined {
+            room_id,
+            player: owner,
+            seat: 1,
+        };
+        self.emit_room_event(room_id, &event).await;
+        self.emit_lobby_event(&event).await;
+
+        let creator_nick = self
+            .state
+            .nicknames
+            .get(&room.creator)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string());
+        let joiner_nick = self
+            .state
+            .nicknames
+            .get(&owner)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string());
+
+        let stake_msg = if stake.is_some() { "💰 Staked match! " } else { "" };
+        self.post_system_message(
+            room_id,
+            &format!(
+                "🎮 {}Game started! {} (X) vs {} (O)",
+                stake_msg, creator_nick, joiner_nick
+            ),
+        )
+        .await;
+
+        Response::Ok
+    }
+
+    async fn make_move(&mut self, owner: AccountOwner, room_id: u32, position: u32, ultimate: bool) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        if room.game.is_ultimate() != ultimate {
+            return Response::Error(if ultimate {
+                GameError::NotUltimate
+            } else {
+                GameError::UltimateMoveRequired
+            });
+        }
+
+        if room.players[room.game.current_player as usize] != Some(owner) {
+            return Response::Error(GameError::NotYourTurn);
+        }
+
+        // a player who moves after their flag fell loses on time
+        let now = self.runtime.system_time().micros();
+        if let Some(clock) = room.clock.as_mut() {
+            if clock.press(now).is_err() {
+                self.post_system_message(room_id, "⏰ Time's up!").await;
+                let winner = rules::player_char(1 - room.game.current_player);
+                self.handle_game_end(room_id, &mut room, winner).await;
+                self.save_room(room_id, &room).await;
+                return Response::GameState(self.get_game_state_response(room_id, &room).await);
+            }
+        }
+
+        let outcome = match room.game.apply_move(position) {
+            Ok(outcome) => outcome,
+            Err(e) => return Response::Error(e.into()),
+        };
+
+        self.record_move(room_id, &mut room, position).await;
+        // making a move declines whatever the opponent had pending
+        room.draw_offer = None;
+        room.takeback_request = None;
+
+        if let Some(outcome) = outcome {
+            self.handle_game_end(room_id, &mut room, outcome.as_char()).await;
+        } else if room.bot.is_some() {
+            self.play_bot_turn(room_id, &mut room).await;
+        } else {
+            let players = room.players;
+            self.announce_turn(room_id, &players, room.game.current_player).await;
+        }
+
+        self.save_room(room_id, &room).await;
+        Response::GameState(self.get_game_state_response(room_id, &room).await)
+    }
+
+    /// Records the result of the game that just ended in `room`. The caller
+    /// saves the room afterwards.
+    async fn handle_game_end(&mut self, room_id: u32, room: &mut Room, result: char) {
+        self.finish_game(room_id, room, result).await;
+
+        // practice games stay out of Elo, the leaderboard and the stakes
+        if room.bot.is_some() {
+            let _ = room.transition(RoomStatus::Finished);
+            return;
+        }
+
+        // Elo and stakes follow the series result, if any
+        let players = room.players;
+        let (settled_players, settled_result) = match room.series.clone() {
+            Some(mut series) => {
+                let winner = match result {
+                    'X' => players[0],
+                    'O' => players[1],
+                    _ => None,
+                };
+                series.record(winner);
+                room.series = Some(series.clone());
+
+                let score = self.series_score(&series).await;
+
+                if !series.is_decided() {
+                    // alternate the first player: the seats swap for every game
+                    room.players = [players[1], players[0]];
+                    self.start_next_game(room_id, room).await;
+                    self.post_system_message(
+                        room_id,
+                        &format!("📊 Series: {} (best of {}). Colors swapped, next game!", score, series.best_of),
+                    )
+                    .await;
+                    return;
+                }
+
+                self.post_system_message(room_id, &format!("🏁 Series over: {}", score))
+                    .await;
+                (series.players, series.result())
+            }
+            None => (players, result),
+        };
+
+        let _ = room.transition(RoomStatus::Finished);
+        self.settle_result(room_id, settled_players, settled_result).await;
+    }
+
+    /// Applies Elo and pays out stakes for the final result of a room, 'X' meaning
+    /// `players[0]` won.
+    async fn settle_result(&mut self, room_id: u32, players: [Option<AccountOwner>; 2], result: char) {
+        match result {
+            'X' => {
+                if let (Some(winner), Some(loser)) = (players[0], players[1]) {
+                    self.update_elo(winner, loser, true).await;
+                }
+            }
+            'O' => {
+                if let (Some(winner), Some(loser)) = (players[1], players[0]) {
+                    self.update_elo(winner, loser, true).await;
+                }
+            }
+            'T' => {
+                if let (Some(p1), Some(p2)) = (players[0], players[1]) {
+                    self.update_elo(p1, p2, false).await;
+                }
+            }
+            _ => {}
+        }
+
+        // stakes leave escrow: the winner takes both, a draw hands each stake back
+        let mut stakes = [0; 2];
+        for (idx, player) in players.iter().enumerate() {
+            if let Some(p) = player {
+                stakes[idx] = self.release_escrow(*p, room_id).await;
+            }
+        }
+        let payouts = match result {
+            'X' => [stakes[0] + stakes[1], 0],
+            'O' => [0, stakes[0] + stakes[1]],
+            _ => stakes,
+        };
+        let kind = match result {
+            'X' | 'O' => TransactionKind::StakeWon { room_id },
+            _ => TransactionKind::StakeRefunded { room_id },
+        };
+        for (player, payout) in players.iter().zip(payouts) {
+            if let Some(p) = player.filter(|_| payout > 0) {
+                self.credit(p, payout, kind).await;
+            }
+        }
+        if let Some(mut staked_game) = self.state.staked_games.get(&room_id).await.unwrap() {
+            for (claimed, payout) in staked_game.claimed.iter_mut().zip(payouts) {
+                *claimed |= payout > 0;
+            }
+            let _ = self.state.staked_games.insert(&room_id, staked_game);
+        }
+    }
+
+    async fn offer_rematch(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::Finished]) {
+            return Response::Error(e);
+        }
+
+        // the bot always agrees to another game
+        if room.bot.is_some() {
+            room.rematch_offer = Some(self.bot_owner());
+            self.save_room(room_id, &room).await;
+            return self.accept_rematch(owner, room_id).await;
+        }
+
+        match room.rematch_offer {
+            Some(offerer) if offerer == owner => {
+                return Response::Error(GameError::OfferPending { kind: OfferKind::Rematch });
+            }
+            // both players want a rematch: the second offer accepts the first
+            Some(_) => return self.accept_rematch(owner, room_id).await,
+            None => {}
+        }
+
+        room.rematch_offer = Some(owner);
+        self.save_room(room_id, &room).await;
+        let nickname = self.nickname(owner).await;
+        self.post_system_message(room_id, &format!("🔁 {} offers a rematch", nickname))
+            .await;
+        Response::Ok
+    }
+
+    async fn accept_rematch(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::Finished]) {
+            return Response::Error(e);
+        }
+
+        let Some(offerer) = room.rematch_offer else {
+            return Response::Error(GameError::NoOffer { kind: OfferKind::Rematch });
+        };
+        if offerer == owner {
+            return Response::Error(GameError::OwnOffer { kind: OfferKind::Rematch });
+        }
+
+        let players = room.players;
+
+        // both stakes are checked before anything is escrowed
+        let stake = room.stake;
+        if let Some(amount) = stake {
+            for p in players.iter().flatten() {
+                let balance = self.state.player_balances.get(p).await.unwrap().unwrap_or(0);
+                if balance < amount {
+                    return Response::Error(GameError::InsufficientBalance {
+                        required: amount,
+                        available: balance,
+                    });
+                }
+            }
+        }
+
+        if let Err(e) = room.transition(RoomStatus::InProgress) {
+            return Response::Error(e);
+        }
+
+        if let Some(amount) = stake {
+            for p in players.iter().flatten() {
+                self.escrow_stake(*p, room_id, amount).await;
+            }
+            let staked_game = StakedGame {
+                room_id,
+                total_pot: amount * 2,
+                players_stake: [amount, amount],
+                claimed: [false, false],
+            };
+            let _ = self.state.staked_games.insert(&room_id, staked_game);
+        }
+
+        // colors swap: last game's O moves first as X
+        let swapped = [players[1], players[0]];
+        room.players = swapped;
+        room.rematch_offer = None;
+        if let Some(series) = room.series.as_mut() {
+            *series = MatchSeries::new(series.best_of, swapped);
+        }
+        self.start_next_game(room_id, &mut room).await;
+
+        let player_x = match swapped[0] {
+            Some(p) => self.nickname(p).await,
+            None => "Anonymous".to_string(),
+        };
+        let player_o = match swapped[1] {
+            Some(p) => self.nickname(p).await,
+            None => "Anonymous".to_string(),
+        };
+        self.post_system_message(
+            room_id,
+            &format!("🔁 Rematch! Colors swapped: {} (X) vs {} (O)", player_x, player_o),
+        )
+        .await;
+        // with colors swapped the bot may be the one to open
+        self.play_bot_turn(room_id, &mut room).await;
+
+        self.save_room(room_id, &room).await;
+        Response::GameState(self.get_game_state_response(room_id, &room).await)
+    }
+
+    /// Leaves a room. Leaving a game in progress forfeits it, and the series
+    /// with it; leaving a room nobody has joined yet cancels it.
+    async fn leave_room(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if let Err(e) = room.require_status(&[
+            RoomStatus::WaitingForOpponent,
+            RoomStatus::InProgress,
+            RoomStatus::Finished,
+            RoomStatus::Abandoned,
+        ]) {
+            return Response::Error(e);
+        }
+        let Some(seat) = room.seat_of(owner) else {
+            return Response::Error(GameError::NotInRoom);
+        };
+        if room.status == RoomStatus::WaitingForOpponent {
+            return self.cancel_room(owner, room_id).await;
+        }
+
+        let player_nickname = self
+            .state
+            .nicknames
+            .get(&owner)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string());
+
+        let mut remaining = room.players;
+        remaining[seat] = None;
+        let next_status = if remaining.iter().all(Option::is_none) {
+            RoomStatus::Closed
+        } else {
+            RoomStatus::Abandoned
+        };
+        let forfeits = room.status == RoomStatus::InProgress && remaining.iter().any(Option::is_some);
+        if let Err(e) = room.transition(next_status) {
+            return Response::Error(e);
+        }
+
+        if forfeits {
+            self.forfeit(room_id, &mut room, seat).await;
+        }
+        room.players = remaining;
+        self.save_room(room_id, &room).await;
+
+        let event = GameEvent::PlayerLeft { room_id, player: owner };
+        self.emit_room_event(room_id, &event).await;
+        self.emit_lobby_event(&event).await;
+
+        self.post_system_message(room_id, &format!("👋 {} left the game", player_nickname))
+            .await;
+        Response::Ok
+    }
+
+    /// Closes a room nobody has joined yet and refunds the creator's stake.
+    async fn cancel_room(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if room.creator != owner {
+            return Response::Error(GameError::NotRoomCreator);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::WaitingForOpponent]) {
+            return Response::Error(e);
+        }
+        if let Err(e) = room.transition(RoomStatus::Closed) {
+            return Response::Error(e);
+        }
+
+        room.players = [None, None];
+        self.save_room(room_id, &room).await;
+
+        let refund = self.release_escrow(owner, room_id).await;
+        if refund > 0 {
+            self.credit(owner, refund, TransactionKind::StakeRefunded { room_id }).await;
+        }
+
+        let event = GameEvent::RoomClosed { room_id };
+        self.emit_room_event(room_id, &event).await;
+        self.emit_lobby_event(&event).await;
+
+        let msg = if refund > 0 {
+            format!("🚫 Room cancelled, {} tokens refunded", refund)
+        } else {
+            "🚫 Room cancelled".to_string()
+        };
+        self.post_system_message(room_id, &msg).await;
+        Response::Ok
+    }
+
+    /// Resigns the current game. In a series only that game is lost; the
+    /// series goes on unless the result decides it.
+    async fn surrender(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        let Some(seat) = room.seat_of(owner) else {
+            return Response::Error(GameError::NotInRoom);
+        };
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        let loser_nick = self
+            .state
+            .nicknames
+            .get(&owner)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string());
+        self.post_system_message(room_id, &format!("🏳️ {} surrendered!", loser_nick))
+            .await;
+
+        let winner = rules::player_char(1 - seat as u8);
+        self.handle_game_end(room_id, &mut room, winner).await;
+        self.save_room(room_id, &room).await;
+        Response::Ok
+    }
+
+    /// Ends the current game, and the series if any, as a loss for the player
+    /// in `loser_seat`: what leaving a game in progress costs. Stats, Elo and
+    /// stakes settle as for a normal win by the opponent. The caller sets the
+    /// room's status and saves it.
+    async fn forfeit(&mut self, room_id: u32, room: &mut Room, loser_seat: usize) {
+        let winner_seat = 1 - loser_seat;
+        let result = rules::player_char(winner_seat as u8);
+        self.finish_game(room_id, room, result).await;
+        if room.bot.is_some() {
+            return;
+        }
+
+        // forfeiting a game forfeits the whole series, however it stood
+        let players = room.players;
+        let (settled_players, settled_result) = match room.series.as_mut() {
+            Some(series) => {
+                series.record(players[winner_seat]);
+                let result = if series.players[0] == players[winner_seat] { 'X' } else { 'O' };
+                (series.players, result)
+            }
+            None => (players, result),
+        };
+        self.settle_result(room_id, settled_players, settled_result).await;
+    }
+
+    /// What every ending of a game has in common, however it came about: the
+    /// result, the clock, the replay, the announcement and the per-game stats.
+    /// Elo, the series and the stakes are left to the caller.
+    async fn finish_game(&mut self, room_id: u32, room: &mut Room, result: char) {
+        room.winner = Some(result);
+        room.draw_offer = None;
+        room.takeback_request = None;
+        if let Some(clock) = room.clock.as_mut() {
+            clock.stop(self.runtime.system_time().micros());
+        }
+        self.record_replay(room_id, room).await;
+        let players = room.players;
+
+        let event = GameEvent::GameEnded { room_id, result };
+        self.emit_room_event(room_id, &event).await;
+        self.emit_lobby_event(&event).await;
+
+        let msg = match result {
+            'X' => "Player X wins! 🏆",
+            'O' => "Player O wins! 🏆",
+            'T' => "It's a tie! 🤝",
+            _ => "Game over!",
+        };
+        self.post_system_message(room_id, msg).await;
+
+        // practice games count in their own stats only
+        if room.bot.is_some() {
+            let bot = self.bot_owner();
+            for (idx, player) in players.iter().enumerate() {
+                if let Some(p) = player.filter(|p| *p != bot) {
+                    let won = result == rules::player_char(idx as u8);
+                    self.update_practice_stats(p, won, result == 'T').await;
+                }
+            }
+            return;
+        }
+
+        match result {
+            'X' | 'O' => {
+                let (winner, loser) = if result == 'X' {
+                    (players[0], players[1])
+                } else {
+                    (players[1], players[0])
+                };
+                if let (Some(winner), Some(loser)) = (winner, loser) {
+                    self.update_player_stats(winner, true, false).await;
+                    self.update_player_stats(loser, false, false).await;
+                }
+            }
+            'T' => {
+                for p in players.iter().flatten() {
+                    self.update_player_stats(*p, false, true).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn claim_timeout(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        let Some(claimer_idx) = room.seat_of(owner) else {
+            return Response::Error(GameError::NotInRoom);
+        };
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        let to_move = room.game.current_player;
+        if to_move as usize == claimer_idx {
+            return Response::Error(GameError::OwnTurn);
+        }
+
+        let now = self.runtime.system_time().micros();
+        if let Some(clock) = &room.clock {
+            if clock.flagged(now) != Some(to_move) {
+                return Response::Error(GameError::OpponentHasTime);
+            }
+        } else {
+            let timeout = self.state.config.get().abandon_timeout_secs.saturating_mul(1_000_000);
+            if now.saturating_sub(room.last_move_at) < timeout {
+                return Response::Error(GameError::OpponentHasTime);
+            }
+        }
+
+        self.post_system_message(room_id, "⏰ Time's up!").await;
+        self.handle_game_end(room_id, &mut room, rules::player_char(claimer_idx as u8))
+            .await;
+        self.save_room(room_id, &room).await;
+        Response::GameState(self.get_game_state_response(room_id, &room).await)
+    }
+
+    async fn offer_draw(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        match room.draw_offer {
+            Some(offerer) if offerer == owner => {
+                return Response::Error(GameError::OfferPending { kind: OfferKind::Draw });
+            }
+            // mutual offers are an agreed draw
+            Some(_) => return self.accept_draw(owner, room_id).await,
+            None => {}
+        }
+
+        room.draw_offer = Some(owner);
+        self.save_room(room_id, &room).await;
+        let nickname = self.nickname(owner).await;
+        self.post_system_message(room_id, &format!("🤝 {} offers a draw", nickname))
+            .await;
+        Response::Ok
+    }
+
+    async fn accept_draw(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        let Some(offerer) = room.draw_offer else {
+            return Response::Error(GameError::NoOffer { kind: OfferKind::Draw });
+        };
+        if offerer == owner {
+            return Response::Error(GameError::OwnOffer { kind: OfferKind::Draw });
+        }
+
+        room.draw_offer = None;
+        self.post_system_message(room_id, "🤝 Draw agreed").await;
+        self.handle_game_end(room_id, &mut room, 'T').await;
+        self.save_room(room_id, &room).await;
+        Response::GameState(self.get_game_state_response(room_id, &room).await)
+    }
+
+    async fn request_takeback(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        let Some(requester_idx) = room.seat_of(owner) else {
+            return Response::Error(GameError::NotInRoom);
+        };
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        // power-ups are not part of the move history, so those boards cannot be rebuilt
+        if matches!(room.mode, GameMode::PowerUp) {
+            return Response::Error(GameError::TakebacksUnavailable);
+        }
+        if self.room_move_count(room_id).await <= room.first_move {
+            return Response::Error(GameError::NothingToTakeBack);
+        }
+        if room.game.current_player as usize == requester_idx {
+            return Response::Error(GameError::NotLastMover);
+        }
+        if room.takeback_request.is_some() {
+            return Response::Error(GameError::OfferPending { kind: OfferKind::Takeback });
+        }
+
+        room.takeback_request = Some(owner);
+        self.save_room(room_id, &room).await;
+        let nickname = self.nickname(owner).await;
+        self.post_system_message(room_id, &format!("↩️ {} asks to take back their last move", nickname))
+            .await;
+        Response::Ok
+    }
+
+    async fn accept_takeback(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        let Some(requester) = room.takeback_request else {
+            return Response::Error(GameError::NoOffer { kind: OfferKind::Takeback });
+        };
+        if requester == owner {
+            return Response::Error(GameError::OwnOffer { kind: OfferKind::Takeback });
+        }
+
+        let mut moves = self.current_game_moves(room_id, &room).await;
+        if moves.pop().is_none() {
+            return Response::Error(GameError::NothingToTakeBack);
+        }
+        // replaying the remaining moves restores the board and the side to move
+        let game = match GameState::from_moves(&room.mode, &moves) {
+            Ok(game) => game,
+            Err(e) => return Response::Error(e.into()),
+        };
+
+        let room_moves = self.state.room_moves.load_entry_mut(&room_id).await.unwrap();
+        let last = *room_moves.count.get() - 1;
+        let _ = room_moves.positions.remove(&last);
+        let _ = room_moves.timestamps.remove(&last);
+        room_moves.count.set(last);
+        let now = self.runtime.system_time().micros();
+        if let Some(clock) = room.clock.as_mut() {
+            clock.stop(now);
+            clock.start(game.current_player, now);
+        }
+        room.game = game;
+        room.last_move_at = now;
+        room.takeback_request = None;
+        self.save_room(room_id, &room).await;
+
+        self.post_system_message(room_id, "↩️ Takeback accepted").await;
+        Response::GameState(self.get_game_state_response(room_id, &room).await)
+    }
+
+    async fn post_message(&mut self, owner: AccountOwner, text: String, room_id: u32) -> Response {
+        if !self.is_player_in_room(owner, room_id).await {
+            return Response::Error(GameError::NotInRoom);
+        }
+
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed.len() > 500 {
+            return Response::Error(GameError::invalid_length("message", 1, 500));
+        }
+
+        let sender = self
+            .state
+            .nicknames
+            .get(&owner)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string());
+
+        let msg = ChatMessage {
+            sender,
+            text: trimmed.to_string(),
+            timestamp: self.runtime.system_time().micros(),
+        };
+        self.push_chat(room_id, msg).await;
+
+        Response::Ok
+    }
+
+    // ===================== TOURNAMENTS =====================
+
+    async fn create_tournament(
+        &mut self,
+        owner: AccountOwner,
+        name: String,
+        format: TournamentFormat,
+        entry_fee: Option<u64>,
+        max_players: u32,
+        prize_distribution: Vec<u32>,
+    ) -> Response {
+        if name.trim().is_empty() || name.len() > 50 {
+            return Response::Error(GameError::invalid_length("tournament_name", 1, 50));
+        }
+        if max_players < 2 || max_players > 256 {
+            return Response::Error(GameError::InvalidPlayerLimit { min: 2, max: 256 });
+        }
+        if let Err(e) = Self::validate_prize_distribution(&prize_distribution) {
+            return Response::Error(e);
+        }
+
+        if let Some(fee) = entry_fee {
+            let bal = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
+            if bal < fee {
+                return Response::Error(GameError::InsufficientBalance {
+                    required: fee,
+                    available: bal,
+                });
+            }
+        }
+
+        let tournament_id = *self.state.next_tournament_id.get();
+        let _ = self.state.next_tournament_id.set(tournament_id + 1);
+
+        if let Some(fee) = entry_fee {
+            self.debit(owner, fee, TransactionKind::EntryFee { tournament_id }).await;
+        }
+
+        let _ = self.state.tournament_names.insert(&tournament_id, name.clone());
+        let _ = self.state.tournament_formats.insert(&tournament_id, format);
+        let _ = self
+            .state
+            .tournament_status
+            .insert(&tournament_id, TournamentStatus::Registration);
+        let _ = self.state.tournament_entry_fees.insert(&tournament_id, entry_fee);
+        let _ = self
+            .state
+            .tournament_players
+            .insert(&tournament_id, vec![owner]);
+        let _ = self
+            .state
+            .tournament_prize_pools
+            .insert(&tournament_id, entry_fee.unwrap_or(0));
+        let _ = self
+            .state
+            .tournament_prize_distributions
+            .insert(&tournament_id, prize_distribution);
+
+        Response::TournamentCreated { id: tournament_id, name }
+    }
+
+    async fn join_tournament(&mut self, owner: AccountOwner, tournament_id: u64) -> Response {
+        let status = self.state.tournament_status.get(&tournament_id).await.unwrap();
+        if status != Some(TournamentStatus::Registration) {
+            return Response::Error(GameError::RegistrationClosed);
+        }
+
+        let Some(mut players) = self.state.tournament_players.get(&tournament_id).await.unwrap() else {
+            return Response::Error(GameError::TournamentNotFound { tournament_id });
+        };
+
+        if players.contains(&owner) {
+            return Response::Error(GameError::AlreadyRegistered);
+        }
+
+        let entry_fee = self.state.tournament_entry_fees.get(&tournament_id).await.unwrap().flatten();
+        if let Some(fee) = entry_fee {
+            let bal = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
+            if bal < fee {
+                return Response::Error(GameError::InsufficientBalance {
+                    required: fee,
+                    available: bal,
+                });
+            }
+            self.debit(owner, fee, TransactionKind::EntryFee { tournament_id }).await;
+
+            let pool = self.state.tournament_prize_pools.get(&tournament_id).await.unwrap().unwrap_or(0);
+            let _ = self.state.tournament_prize_pools.insert(&tournament_id, pool + fee);
+        }
+
+        players.push(owner);
+        let _ = self.state.tournament_players.insert(&tournament_id, players.clone());
+
+        Response::TournamentJoined {
+            id: tournament_id,
+            position: players.len() as u32,
+        }
+    }
+
+    async fn start_tournament(&mut self, owner: AccountOwner, tournament_id: u64) -> Response {
+        let Some(players) = self.state.tournament_players.get(&tournament_id).await.unwrap() else {
+            return Response::Error(GameError::TournamentNotFound { tournament_id });
+        };
+
+        if players.first() != Some(&owner) {
+            return Response::Error(GameError::NotTournamentCreator);
+        }
+        if players.len() < 2 {
+            return Response::Error(GameError::NotEnoughPlayers { min: 2 });
+        }
+
+        let Some(format) = self.state.tournament_formats.get(&tournament_id).await.unwrap() else {
+            return Response::Error(GameError::TournamentNotFound { tournament_id });
+        };
+
+        let bracket = match format {
+            TournamentFormat::SingleElimination => self.generate_single_elimination_bracket(&players).await,
+            TournamentFormat::Swiss(rounds) => self.generate_swiss_pairings(&players, rounds).await,
+            TournamentFormat::RoundRobin => self.generate_round_robin_bracket(&players).await,
+        };
+
+        let _ = self.state.tournament_brackets.insert(&tournament_id, bracket);
+        let _ = self
+            .state
+            .tournament_status
+            .insert(&tournament_id, TournamentStatus::InProgress);
+        self.emit_tournament_progress(tournament_id).await;
+
+        Response::Ok
+    }
+
+    async fn report_match_result(
+        &mut self,
+        owner: AccountOwner,
+        tournament_id: u64,
+        match_id: u64,
+        result: MatchResult,
+    ) -> Response {
+        let Some(mut bracket) = self.state.tournament_brackets.get(&tournament_id).await.unwrap() else {
+            return Response::Error(GameError::BracketNotFound { tournament_id });
+        };
+
+        let Some(idx) = bracket.iter().position(|m| m.match_id == match_id) else {
+            return Response::Error(GameError::MatchNotFound { match_id });
+        };
+
+        let is_p1 = bracket[idx].player1 == Some(owner);
+        let is_p2 = bracket[idx].player2 == Some(owner);
+        if !is_p1 && !is_p2 {
+            return Response::Error(GameError::NotMatchParticipant);
+        }
+
+        bracket[idx].result = Some(result);
+
+        let _ = self.state.tournament_brackets.insert(&tournament_id, bracket);
+
+        self.check_tournament_completion(tournament_id).await;
+        self.emit_tournament_progress(tournament_id).await;
+        Response::Ok
+    }
+
+    // ===================== ECONOMY =====================
+
+    /// Moves native tokens from the signer's account into the application's
+    /// account and credits them to the signer's balance. Every balance is backed
+    /// by tokens the application holds.
+    async fn deposit_tokens(&mut self, owner: AccountOwner, amount: u64) -> Response {
+        if amount == 0 {
+            return Response::Error(GameError::InvalidAmount);
+        }
+        let tokens = Amount::from_tokens(amount.into());
+        if self.runtime.owner_balance(owner) < tokens {
+            return Response::Error(GameError::InsufficientFunds);
+        }
+
+        let destination = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: self.application_owner(),
+        };
+        self.runtime.transfer(owner, destination, tokens);
+
+        self.credit(owner, amount, TransactionKind::Deposit).await;
+        Response::OkWithData(format!("Deposited {} tokens", amount))
+    }
+
+    /// Debits the signer's balance and pays the tokens back to their account.
+    async fn withdraw_tokens(&mut self, owner: AccountOwner, amount: u64) -> Response {
+        if amount == 0 {
+            return Response::Error(GameError::InvalidAmount);
+        }
+        let cur = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0);
+        if cur < amount {
+            return Response::Error(GameError::InsufficientBalance {
+                required: amount,
+                available: cur,
+            });
+        }
+        self.debit(owner, amount, TransactionKind::Withdraw).await;
+
+        let source = self.application_owner();
+        let destination = Account {
+            chain_id: self.runtime.chain_id(),
+            owner,
+        };
+        self.runtime.transfer(source, destination, Amount::from_tokens(amount.into()));
+        Response::OkWithData(format!("Withdrawn {} tokens", amount))
+    }
+
+    /// Pays out every prize the player has won and not yet claimed.
+    async fn claim_rewards(&mut self, owner: AccountOwner) -> Response {
+        let mut total_claimed = 0u64;
+        let tournament_ids: Vec<u64> = self.state.tournament_names.indices().await.unwrap_or_default();
+
+        for tid in tournament_ids {
+            let Some(winners) = self.state.tournament_winners.get(&tid).await.unwrap() else { continue; };
+            let Some(place) = winners.iter().position(|w| *w == owner) else { continue; };
+            if self.state.tournament_claims.get(&(tid, owner)).await.unwrap().is_some() {
+                continue;
+            }
+
+            let pool = self.state.tournament_prize_pools.get(&tid).await.unwrap().unwrap_or(0);
+            let distribution = self
+                .state
+                .tournament_prize_distributions
+                .get(&tid)
+                .await
+                .unwrap()
+                .unwrap_or_else(|| LEGACY_PRIZE_DISTRIBUTION.to_vec());
+            let prize = Self::prize_shares(pool, &distribution, winners.len())[place];
+
+            let _ = self.state.tournament_claims.insert(&(tid, owner), prize);
+            if prize > 0 {
+                self.credit(owner, prize, TransactionKind::PrizePaid { tournament_id: tid }).await;
+                total_claimed += prize;
+            }
+        }
+
+        if total_claimed > 0 {
+            Response::OkWithData(format!("Claimed {} LIN in rewards", total_claimed))
+        } else {
+            Response::Error(GameError::NoRewards)
+        }
+    }
+
+    /// Checks a prize split: at most `PAID_PLACES` entries of at most 100%
+    /// each, adding up to exactly 100%.
+    fn validate_prize_distribution(distribution: &[u32]) -> Result<(), GameError> {
+        if distribution.len() > PAID_PLACES {
+            return Err(GameError::TooManyPrizePlaces { max: PAID_PLACES as u32 });
+        }
+        let total = distribution.iter().try_fold(0u32, |total, &percent| {
+            (percent <= 100).then_some(percent).and_then(|percent| total.checked_add(percent))
+        });
+        if total != Some(100) {
+            let total = distribution.iter().fold(0u32, |total, &percent| total.saturating_add(percent));
+            return Err(GameError::InvalidPrizeDistribution { total });
+        }
+        Ok(())
+    }
+
+    /// Splits `pool` between the first `places` finishers by `distribution`.
+    /// Shares are rounded down; the rounding remainder and the percentages of
+    /// places nobody finished in go to first place, so the shares always add
+    /// up to the whole pool. No share is taken from money already given out,
+    /// so the total never exceeds the pool even for a malformed distribution.
+    fn prize_shares(pool: u64, distribution: &[u32], places: usize) -> Vec<u64> {
+        let mut remaining = pool;
+        let mut shares: Vec<u64> = (0..places)
+            .map(|place| {
+                let percent = distribution.get(place).copied().unwrap_or(0).min(100);
+                let share = ((u128::from(pool) * u128::from(percent) / 100) as u64).min(remaining);
+                remaining -= share;
+                share
+            })
+            .collect();
+        if let Some(first) = shares.first_mut() {
+            *first += remaining;
+        }
+        shares
+    }
+
+    // ===================== GUILDS =====================
+
+    async fn create_guild(&mut self, owner: AccountOwner, name: String, tag: String) -> Response {
+        if name.trim().is_empty() || name.len() > 30 {
+            return Response::Error(GameError::invalid_length("guild_name", 1, 30));
+        }
+        if tag.trim().is_empty() || tag.len() > 5 {
+            return Response::Error(GameError::invalid_length("guild_tag", 1, 5));
+        }
+
+        let guild_ids: Vec<u64> = self.state.guilds.indices().await.unwrap_or_default();
+        for gid in guild_ids {
+            if let Some(g) = self.state.guilds.get(&gid).await.unwrap() {
+                if g.tag == tag {
+                    return Response::Error(GameError::GuildTagTaken);
+                }
+            }
+        }
+
+        let guild_id = *self.state.next_guild_id.get();
+        let _ = self.state.next_guild_id.set(guild_id + 1);
+
+        let guild = Guild {
+            id: guild_id,
+            name: name.clone(),
+            tag: tag.clone(),
+            level: 1,
+            experience: 0,
+            treasury: 0,
+            member_count: 1,
+            created_at: self.runtime.system_time().micros(),
+        };
+
+        let _ = self.state.guilds.insert(&guild_id, guild);
+        let _ = self
+            .state
+            .guild_members
+            .insert(&(guild_id, owner), GuildRole::Leader);
+        let _ = self.state.player_guilds.insert(&owner, guild_id);
+
+        Response::OkWithData(format!("Guild {} [{}] created!", name, tag))
+    }
+
+    async fn join_guild(&mut self, owner: AccountOwner, guild_id: u64) -> Response {
+        if self.state.player_guilds.get(&owner).await.unwrap().is_some() {
+            return Response::Error(GameError::AlreadyInGuild);
+        }
+
+        let Some(mut guild) = self.state.guilds.get(&guild_id).await.unwrap() else {
+            return Response::Error(GameError::GuildNotFound { guild_id });
+        };
+
+        let _ = self
+            .state
+            .guild_members
+            .insert(&(guild_id, owner), GuildRole::Member);
+        let _ = self.state.player_guilds.insert(&owner, guild_id);
+
+        guild.member_count += 1;
+        let _ = self.state.guilds.insert(&guild_id, guild);
+
+        Response::Ok
+    }
+
+    async fn invite_to_guild(&mut self, inviter: AccountOwner, player: AccountOwner, guild_id: u64) -> Response {
+        let Some(role) = self.state.guild_members.get(&(guild_id, inviter)).await.unwrap() else {
+            return Response::Error(GameError::NotInGuild);
+        };
+
+        match role {
+            GuildRole::Leader | GuildRole::Officer => {}
+            _ => return Response::Error(GameError::InsufficientPermissions),
+        }
+
+        if self.state.player_guilds.get(&player).await.unwrap().is_some() {
+            return Response::Error(GameError::PlayerAlreadyInGuild);
+        }
+
+        self.join_guild(player, guild_id).await
+    }
+
+    // ===================== ADVANCED =====================
+
+    async fn use_powerup(&mut self, owner: AccountOwner, room_id: u32, power_up: PowerUpType) -> Response {
+        let Some(mut room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        if let Err(e) = room.require_status(&[RoomStatus::InProgress]) {
+            return Response::Error(e);
+        }
+
+        if !matches!(room.game.mode, GameMode::PowerUp) {
+            return Response::Error(GameError::PowerUpsDisabled);
+        }
+
+        let powerup_count = self
+            .state
+            .player_powerups
+            .get(&(owner, power_up))
+            .await
+            .unwrap()
+            .unwrap_or(0);
+
+        if powerup_count == 0 {
+            return Response::Error(GameError::PowerUpNotOwned);
+        }
+
+        if room.players[room.game.current_player as usize] != Some(owner) {
+            return Response::Error(GameError::NotYourTurn);
+        }
+
+        if let Err(e) = room.game.apply_power_up(&power_up) {
+            return Response::Error(e.into());
+        }
+        self.save_room(room_id, &room).await;
+
+        let _ = self
+            .state
+            .player_powerups
+            .insert(&(owner, power_up), powerup_count - 1);
+
+        self.post_system_message(room_id, "⚡ Power-up used").await;
+        Response::Ok
+    }
+
+    async fn save_replay(&mut self, owner: AccountOwner, room_id: u32) -> Response {
+        let Some(room) = self.load_room(room_id).await else {
+            return Response::Error(GameError::RoomNotFound { room_id });
+        };
+        if !room.has_player(owner) {
+            return Response::Error(GameError::NotInRoom);
+        }
+        let Some(replay_id) = room.last_replay else {
+            return Response::Error(GameError::NoFinishedGame);
+        };
+        let Some(mut replay) = self.state.game_replays.get(&replay_id).await.unwrap() else {
+            return Response::Error(GameError::ReplayUnavailable);
+        };
+        let Some(seat) = replay.players.iter().position(|p| *p == Some(owner)) else {
+            return Response::Error(GameError::NotAPlayer);
+        };
+
+        if !replay.kept[seat] {
+            return Response::Error(GameError::ReplayUnavailable);
+        }
+
+        if !replay.pinned[seat] {
+            replay.pinned[seat] = true;
+            let _ = self.state.game_replays.insert(&replay_id, replay);
+            let mut head = self
+                .state
+                .player_replay_heads
+                .get(&owner)
+                .await
+                .unwrap()
+                .unwrap_or_default();
+            head.unpinned = head.unpinned.saturating_sub(1);
+            let _ = self.state.player_replay_heads.insert(&owner, head);
+        }
+
+        Response::OkWithData(format!("Replay saved with ID: {}", replay_id))
+    }
+
+    /// Stores the game that just ended in `room` as a replay for both players.
+    async fn record_replay(&mut self, room_id: u32, room: &mut Room) {
+        let players = room.players;
+        let moves = self.current_game_moves(room_id, room).await;
+        let move_times = self.current_game_move_times(room_id, room).await;
+        let end_time = self.runtime.system_time().micros();
+        let duration = end_time.saturating_sub(room.started_at);
+
+        let replay_id = *self.state.next_replay_id.get();
+        self.state.next_replay_id.set(replay_id + 1);
+
+        // the bot keeps no replays
+        let bot = self.bot_owner();
+        let kept = players.map(|player| player.is_some_and(|p| p != bot));
+        let replay = GameReplay {
+            room_id,
+            mode: room.mode.clone(),
+            moves,
+            move_times,
+            players,
+            winner: room.winner,
+            stake: room.stake,
+            started_at: room.started_at,
+            duration,
+            timestamp: end_time,
+            pinned: [false, false],
+            kept,
+        };
+        let _ = self.state.game_replays.insert(&replay_id, replay);
+        room.last_replay = Some(replay_id);
+
+        for (seat, player) in players.into_iter().enumerate() {
+            let Some(player) = player.filter(|_| kept[seat]) else {
+                continue;
+            };
+            let mut head = self
+                .state
+                .player_replay_heads
+                .get(&player)
+                .await
+                .unwrap()
+                .unwrap_or_default();
+            let _ = self
+                .state
+                .player_replay_index
+                .insert(&(player, head.next_seq), replay_id);
+            head.next_seq += 1;
+            head.unpinned += 1;
+            let _ = self.state.player_replay_heads.insert(&player, head);
+            self.prune_replays(player).await;
+        }
+    }
+
+    /// Drops the player's oldest unpinned replays beyond the configured
+    /// retention. A replay is deleted once no player keeps it.
+    async fn prune_replays(&mut self, player: AccountOwner) {
+        let retention = self.state.config.get().replay_retention;
+        let Some(mut head) = self.state.player_replay_heads.get(&player).await.unwrap() else {
+            return;
+        };
+
+        while head.unpinned > retention && head.oldest_unpinned < head.next_seq {
+            let seq = head.oldest_unpinned;
+            head.oldest_unpinned += 1;
+            let Some(replay_id) = self.state.player_replay_index.get(&(player, seq)).await.unwrap() else {
+                continue;
+            };
+            let Some(mut replay) = self.state.game_replays.get(&replay_id).await.unwrap() else {
+                let _ = self.state.player_replay_index.remove(&(player, seq));
+                continue;
+            };
+            if replay.is_pinned_by(player) {
+                continue;
+            }
+
+            let _ = self.state.player_replay_index.remove(&(player, seq));
+            head.unpinned -= 1;
+            for (seat, p) in replay.players.iter().enumerate() {
+                if *p == Some(player) {
+                    replay.kept[seat] = false;
+                }
+            }
+            if replay.kept.contains(&true) {
+                let _ = self.state.game_replays.insert(&replay_id, replay);
+            } else {
+                let _ = self.state.game_replays.remove(&replay_id);
+            }
+        }
+        let _ = self.state.player_replay_heads.insert(&player, head);
+    }
+
+    async fn challenge_player(&mut self, challenger: AccountOwner, player: AccountOwner, stake: Option<u64>) -> Response {
+        if challenger == player {
+            return Response::Error(GameError::CannotChallengeSelf);
+        }
+
+        let player_exists = self.state.player_stats.get(&player).await.unwrap().is_some();
+        if !player_exists {
+            return Response::Error(GameError::PlayerNotFound);
+        }
+
+        let challenger_name = self
+            .state
+            .nicknames
+            .get(&challenger)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Player1".to_string());
+        let player_name = self
+            .state
+            .nicknames
+            .get(&player)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Player2".to_string());
+
+        let room_name = format!("Challenge: {} vs {}", challenger_name, player_name);
+
+        let settings = MatchSettings {
+            mode: GameMode::Classic,
+            increment: None,
+            series_length: None,
+        };
+        self.create_match(challenger, room_name, None, stake, settings).await
+    }
+
+    // ===================== HELPERS =====================
+
+    async fn load_room(&self, room_id: u32) -> Option<Room> {
+        self.state
+            .rooms
+            .try_load_entry(&room_id)
+            .await
+            .unwrap()
+            .and_then(|entry| entry.record.get().clone())
+    }
+
+    /// Stores the room and announces a change of its status, whatever caused it.
+    async fn save_room(&mut self, room_id: u32, room: &Room) {
+        let entry = self.state.rooms.load_entry_mut(&room_id).await.unwrap();
+        let previous = entry.record.get().as_ref().map(|saved| saved.status);
+        entry.record.set(Some(room.clone()));
+
+        if previous.is_some_and(|status| status != room.status) {
+            let event = GameEvent::RoomStatusChanged {
+                room_id,
+                status: room.status,
+            };
+            self.emit_room_event(room_id, &event).await;
+            self.emit_lobby_event(&event).await;
+        }
+    }
+
+    /// The application's own account, which holds every deposited token.
+    fn application_owner(&mut self) -> AccountOwner {
+        AccountOwner::from(self.runtime.application_id().forget_abi())
+    }
+
+    /// The seat of the built-in bot: the application's own account.
+    fn bot_owner(&mut self) -> AccountOwner {
+        self.application_owner()
+    }
+
+    /// Moves `amount` from the owner's balance into escrow for `room_id`. The
+    /// caller has already checked the balance.
+    async fn escrow_stake(&mut self, owner: AccountOwner, room_id: u32, amount: u64) {
+        self.debit(owner, amount, TransactionKind::StakeLocked { room_id }).await;
+        let escrow = self.state.room_escrow.load_entry_mut(&owner).await.unwrap();
+        let held = escrow.get(&room_id).await.unwrap().unwrap_or(0);
+        let _ = escrow.insert(&room_id, held + amount);
+    }
+
+    /// Takes the owner's stake for `room_id` out of escrow and returns it; the
+    /// caller decides who gets paid.
+    async fn release_escrow(&mut self, owner: AccountOwner, room_id: u32) -> u64 {
+        let escrow = self.state.room_escrow.load_entry_mut(&owner).await.unwrap();
+        let held = escrow.get(&room_id).await.unwrap().unwrap_or(0);
+        let _ = escrow.remove(&room_id);
+        held
+    }
+
+    /// Adds `amount` to the owner's balance and records why in their ledger.
+    /// Every balance change goes through here or `debit`.
+    async fn credit(&mut self, owner: AccountOwner, amount: u64, kind: TransactionKind) {
+        let balance = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0) + amount;
+        let _ = self.state.player_balances.insert(&owner, balance);
+        self.record_transaction(owner, kind, amount, balance).await;
+    }
+
+    /// Takes `amount` from the owner's balance; the caller has checked it is there.
+    async fn debit(&mut self, owner: AccountOwner, amount: u64, kind: TransactionKind) {
+        let balance = self.state.player_balances.get(&owner).await.unwrap().unwrap_or(0) - amount;
+        let _ = self.state.player_balances.insert(&owner, balance);
+        self.record_transaction(owner, kind, amount, balance).await;
+    }
+
+    async fn record_transaction(
+        &mut self,
+        owner: AccountOwner,
+        kind: TransactionKind,
+        amount: u64,
+        balance: u64,
+    ) {
+        let timestamp = self.runtime.system_time().micros();
+        let ledger = self.state.ledgers.load_entry_mut(&owner).await.unwrap();
+        let seq = *ledger.count.get();
+        let _ = ledger.entries.insert(&seq, Transaction { kind, amount, balance, timestamp });
+        ledger.count.set(seq + 1);
+    }
+
+    /// Plays the bot's move in a practice room when it is the bot's turn.
+    async fn play_bot_turn(&mut self, room_id: u32, room: &mut Room) {
+        let Some(difficulty) = room.bot else {
+            return;
+        };
+        if room.players[room.game.current_player as usize] != Some(self.bot_owner()) {
+            return;
+        }
+
+        // no randomness on chain: the block time and the ply pick among equal moves
+        let move_count = self.room_move_count(room_id).await;
+        let seed = self.runtime.system_time().micros()
+            ^ (u64::from(room_id) << 32)
+            ^ u64::from(move_count);
+        let cells = room.game.display_board();
+        let Some(position) = solver::bot_move(&cells, room.game.to_move(), difficulty, seed) else {
+            return;
+        };
+        let Ok(outcome) = room.game.apply_move(position) else {
+            return;
+        };
+
+        self.record_move(room_id, room, position).await;
+        if let Some(outcome) = outcome {
+            self.handle_game_end(room_id, room, outcome.as_char()).await;
+        }
+    }
+
+    async fn is_player_in_room(&self, owner: AccountOwner, room_id: u32) -> bool {
+        self.load_room(room_id)
+            .await
+            .is_some_and(|room| room.has_player(owner))
+    }
+
+    async fn update_player_stats(&mut self, player: AccountOwner, is_win: bool, is_draw: bool) {
+        let (mut wins, mut losses, mut draws) = self
+            .state
+            .player_stats
+            .get(&player)
+            .await
+            .unwrap()
+            .unwrap_or((0, 0, 0));
+
+        if is_win {
+            wins += 1;
+            let cur = self.state.player_streaks.get(&player).await.unwrap().unwrap_or(0);
+            let new_streak = if cur > 0 { cur + 1 } else { 1 };
+            let _ = self.state.player_streaks.insert(&player, new_streak);
+        } else if is_draw {
+            draws += 1;
+        } else {
+            losses += 1;
+            let _ = self.state.player_streaks.insert(&player, 0);
+        }
+
+        let _ = self.state.player_stats.insert(&player, (wins, losses, draws));
+    }
+
+    async fn update_practice_stats(&mut self, player: AccountOwner, is_win: bool, is_draw: bool) {
+        let (mut wins, mut losses, mut draws) = self
+            .state
+            .practice_stats
+            .get(&player)
+            .await
+            .unwrap()
+            .unwrap_or((0, 0, 0));
+        if is_win {
+            wins += 1;
+        } else if is_draw {
+            draws += 1;
+        } else {
+            losses += 1;
+        }
+        let _ = self.state.practice_stats.insert(&player, (wins, losses, draws));
+    }
+
+    async fn update_elo(&mut self, p1: AccountOwner, p2: AccountOwner, p1_won: bool) {
+        let elo1 = self.state.player_elo.get(&p1).await.unwrap().unwrap_or(1500);
+        let elo2 = self.state.player_elo.get(&p2).await.unwrap().unwrap_or(1500);
+
+        let k = 32.0;
+        let expected1 = 1.0 / (1.0 + 10.0_f64.powf((elo2 as f64 - elo1 as f64) / 400.0));
+        let actual1 = if p1_won { 1.0 } else { 0.0 };
+
+        let new1 = (elo1 as f64 + k * (actual1 - expected1)).round().max(0.0) as u32;
+        let new2 = (elo2 as f64 + k * ((1.0 - actual1) - (1.0 - expected1))).round().max(0.0) as u32;
+
+        let _ = self.state.player_elo.insert(&p1, new1);
+        let _ = self.state.player_elo.insert(&p2, new2);
+    }
+
+    /// Starts a fresh game between the room's seated players. Earlier games'
+    /// moves stay in `room_moves`, the new game's moves follow them.
+    async fn start_next_game(&mut self, room_id: u32, room: &mut Room) {
+        let now = self.runtime.system_time().micros();
+        let first_move = self.room_move_count(room_id).await;
+        room.start_next_game(now, first_move);
+
+        let event = GameEvent::GameStarted {
+            room_id,
+            players: room.players,
+        };
+        self.emit_room_event(room_id, &event).await;
+        self.emit_lobby_event(&event).await;
+    }
+
+    async fn room_move_count(&self, room_id: u32) -> u32 {
+        match self.state.room_moves.try_load_entry(&room_id).await.unwrap() {
+            Some(room_moves) => *room_moves.count.get(),
+            None => 0,
+        }
+    }
+
+    async fn current_game_moves(&self, room_id: u32, room: &Room) -> Vec<u32> {
+        let Some(room_moves) = self.state.room_moves.try_load_entry(&room_id).await.unwrap() else {
+            return Vec::new();
+        };
+
+        let mut moves = Vec::new();
+        for move_num in room.first_move..*room_moves.count.get() {
+            if let Some(pos) = room_moves.positions.get(&move_num).await.unwrap() {
+                moves.push(pos);
+            }
+        }
+        moves
+    }
+
+    async fn current_game_move_times(&self, room_id: u32, room: &Room) -> Vec<u64> {
+        let Some(room_moves) = self.state.room_moves.try_load_entry(&room_id).await.unwrap() else {
+            return Vec::new();
+        };
+
+        let mut times = Vec::new();
+        for move_num in room.first_move..*room_moves.count.get() {
+            times.push(room_moves.timestamps.get(&move_num).await.unwrap().unwrap_or(0));
+        }
+        times
+    }
+
+    /// Stores a move that was just applied to `room.game`.
+    async fn record_move(&mut self, room_id: u32, room: &mut Room, position: u32) {
+        let now = self.runtime.system_time().micros();
+        let room_moves = self.state.room_moves.load_entry_mut(&room_id).await.unwrap();
+        let move_num = *room_moves.count.get();
+        let _ = room_moves.positions.insert(&move_num, position);
+        let _ = room_moves.timestamps.insert(&move_num, now);
+        room_moves.count.set(move_num + 1);
+        room.last_move_at = now;
+
+        // the turn only passed if the move didn't end the game
+        let mover = if room.game.outcome().is_some() {
+            room.game.current_player
+        } else {
+            1 - room.game.current_player
+        };
+        self.emit_room_event(
+            room_id,
+            &GameEvent::MoveMade {
+                room_id,
+                player: room.players[mover as usize],
+                mark: rules::player_char(mover),
+                position,
+                move_number: move_num,
+                timestamp: now,
+            },
+        )
+        .await;
+    }
+
+    /// Brings state written by the first release into the current layout.
+    /// Each call moves at most `MIGRATION_BATCH` entries, so a long history
+    /// is spread over several operations instead of running out of fuel in
+    /// one; returns whether the migration has finished.
+    async fn migrate_storage(&mut self) -> bool {
+        if *self.state.storage_version.get() >= STORAGE_VERSION {
+            return true;
+        }
+
+        let mut budget = MIGRATION_BATCH;
+        budget -= self.migrate_move_history(budget).await;
+        if budget > 0 {
+            budget -= self.migrate_rooms(budget).await;
+        }
+        if budget > 0 {
+            budget -= self.migrate_chat(budget).await;
+        }
+        if budget == 0 {
+            return false;
+        }
+
+        // every count now lives in its room's `chat_count`
+        self.state.chat_counts.clear();
+
+        // The first release credited deposits without receiving any tokens, so
+        // none of its money is backed: balances, stakes and prize pools start over.
+        self.state.player_balances.clear();
+        self.state.escrow_accounts.clear();
+        self.state.staked_games.clear();
+        self.state.tournament_prize_pools.clear();
+
+        // first-release replays can't be converted: they lack the mode and move times
+        self.state.legacy_replays.clear();
+
+        // registers added since start where `instantiate` puts them
+        if *self.state.next_replay_id.get() == 0 {
+            self.state.next_replay_id.set(1);
+        }
+        self.state.storage_version.set(STORAGE_VERSION);
+        true
+    }
+
+    /// Moves up to `budget` entries of the flat move history into
+    /// `room_moves` and returns how many it moved. The first release cleared
+    /// a room's history on reset, so what's left is the current game's moves,
+    /// numbered from 0.
+    async fn migrate_move_history(&mut self, budget: usize) -> usize {
+        let mut entries = Vec::new();
+        self.state
+            .game_moves_history
+            .for_each_index_while(|entry| {
+                entries.push(entry);
+                Ok(entries.len() < budget)
+            })
+            .await
+            .unwrap();
+
+        for &(room_id, move_num) in &entries {
+            let Some(position) = self.state.game_moves_history.get(&(room_id, move_num)).await.unwrap() else {
+                continue;
+            };
+            let room_moves = self.state.room_moves.load_entry_mut(&room_id).await.unwrap();
+            let _ = room_moves.positions.insert(&move_num, position);
+            if move_num >= *room_moves.count.get() {
+                room_moves.count.set(move_num + 1);
+            }
+            let _ = self.state.game_moves_history.remove(&(room_id, move_num));
+        }
+        entries.len()
+    }
+
+    /// Turns up to `budget` first-release rooms into `Room` records and
+    /// returns how many it handled.
+    async fn migrate_rooms(&mut self, budget: usize) -> usize {
+        let mut room_ids = Vec::new();
+        self.state
+            .room_names
+            .for_each_index_while(|room_id| {
+                room_ids.push(room_id);
+                Ok(room_ids.len() < budget)
+            })
+            .await
+            .unwrap();
+
+        for &room_id in &room_ids {
+            if let Some(room) = self.legacy_room(room_id).await {
+                let entry = self.state.rooms.load_entry_mut(&room_id).await.unwrap();
+                entry.record.set(Some(room));
+            }
+            self.remove_legacy_room(room_id);
+        }
+        room_ids.len()
+    }
+
+    /// Rebuilds a first-release room from its per-field maps.
+    async fn legacy_room(&self, room_id: u32) -> Option<Room> {
+        let state = &self.state;
+        let name = state.room_names.get(&room_id).await.unwrap()?;
+        let creator = state.room_creators.get(&room_id).await.unwrap()?;
+        let password = state.room_passwords.get(&room_id).await.unwrap().flatten();
+        let mode = match state.room_modes.get(&room_id).await.unwrap().unwrap_or(GameMode::Classic) {
+            // the first release limited each move; the limit becomes the time bank
+            GameMode::Speed(secs) => GameMode::Speed(secs.clamp(1, rules::MAX_TIME_BANK_SECS)),
+            mode => mode,
+        };
+        let is_full = state.room_is_full.get(&room_id).await.unwrap().unwrap_or(false);
+        let players = state
+            .game_players
+            .get(&room_id)
+            .await
+            .unwrap()
+            .unwrap_or([Some(creator), None]);
+        let cells = state.game_boards.get(&room_id).await.unwrap().unwrap_or([None; 9]);
+        let current_player = state.game_current_players.get(&room_id).await.unwrap().unwrap_or(0);
+        let winner = state.game_winners.get(&room_id).await.unwrap().flatten();
+        let last_move_at = state.game_move_times.get(&room_id).await.unwrap().unwrap_or(0);
+
+        // the first release only cleared `room_is_full` once both players had left
+        let seated = players.iter().flatten().count();
+        let status = if seated == 0 {
+            RoomStatus::Closed
+        } else if winner.is_some() {
+            RoomStatus::Finished
+        } else if !is_full {
+            RoomStatus::WaitingForOpponent
+        } else if seated == 2 {
+            RoomStatus::InProgress
+        } else {
+            RoomStatus::Abandoned
+        };
+
+        // stakes were paid from unbacked balances, so the room carries none
+        let mut room = Room::new(name, creator, password, mode, None, last_move_at);
+        room.status = status;
+        room.players = players;
+        // every mode was played on a 3x3 board
+        room.game = GameState {
+            mode: room.mode.clone(),
+            board: Board::Classic(cells),
+            current_player: current_player.min(1),
+        };
+        room.winner = winner;
+        if let GameMode::Speed(secs) = room.mode {
+            let mut clock = ChessClock::new(secs, 0);
+            if status == RoomStatus::InProgress {
+                clock.start(room.game.current_player, last_move_at);
+            }
+            room.clock = Some(clock);
+        }
+        Some(room)
+    }
+
+    fn remove_legacy_room(&mut self, room_id: u32) {
+        let state = &mut self.state;
+        let _ = state.room_names.remove(&room_id);
+        let _ = state.room_creators.remove(&room_id);
+        let _ = state.room_passwords.remove(&room_id);
+        let _ = state.room_is_full.remove(&room_id);
+        let _ = state.room_modes.remove(&room_id);
+        let _ = state.room_stakes.remove(&room_id);
+        let _ = state.game_boards.remove(&room_id);
+        let _ = state.game_current_players.remove(&room_id);
+        let _ = state.game_players.remove(&room_id);
+        let _ = state.game_winners.remove(&room_id);
+        let _ = state.game_move_times.remove(&room_id);
+    }
+
+    /// Moves up to `budget` first-release chat messages into their rooms and
+    /// returns how many it moved.
+    async fn migrate_chat(&mut self, budget: usize) -> usize {
+        let mut entries = Vec::new();
+        self.state
+            .chat_messages
+            .for_each_index_while(|entry| {
+                entries.push(entry);
+                Ok(entries.len() < budget)
+            })
+            .await
+            .unwrap();
+
+        for &(room_id, index) in &entries {
+            let Some(message) = self.state.chat_messages.get(&(room_id, index)).await.unwrap() else {
+                continue;
+            };
+            let entry = self.state.rooms.load_entry_mut(&room_id).await.unwrap();
+            let _ = entry.chat.insert(&index, message);
+            if index >= *entry.chat_count.get() {
+                entry.chat_count.set(index + 1);
+            }
+            let _ = self.state.chat_messages.remove(&(room_id, index));
+        }
+        entries.len()
+    }
+
+    async fn announce_turn(&mut self, room_id: u32, players: &[Option<AccountOwner>; 2], next_idx: u8) {
+        let next_name = match players[next_idx as usize] {
+            Some(p) => self
+                .state
+                .nicknames
+                .get(&p)
+                .await
+                .unwrap()
+                .unwrap_or_else(|| "Player".to_string()),
+            None => "Player".to_string(),
+        };
+
+        self.post_system_message(
+            room_id,
+            &format!(
+                "➡️ {}'s turn ({})",
+                next_name,
+                if next_idx == 0 { "X" } else { "O" }
+            ),
+        )
+        .await;
+    }
+
+    async fn nickname(&self, owner: AccountOwner) -> String {
+        self.state
+            .nicknames
+            .get(&owner)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string())
+    }
+
+    async fn series_score(&self, series: &MatchSeries) -> String {
+        let mut names = Vec::new();
+        for player in series.players {
+            names.push(match player {
+                Some(owner) => self.nickname(owner).await,
+                None => "Anonymous".to_string(),
+            });
+        }
+        format!("{} {} - {} {}", names[0], series.wins[0], series.wins[1], names[1])
+    }
+
+    async fn post_system_message(&mut self, room_id: u32, text: &str) {
+        let msg = ChatMessage {
+            sender: "🛠️ SYSTEM".to_string(),
+            text: text.to_string(),
+            timestamp: self.runtime.system_time().micros(),
+        };
+        self.push_chat(room_id, msg).await;
+    }
+
+    async fn push_chat(&mut self, room_id: u32, msg: ChatMessage) {
+        let entry = self.state.rooms.load_entry_mut(&room_id).await.unwrap();
+        let cur = *entry.chat_count.get();
+        let _ = entry.chat.insert(&cur, msg.clone());
+        entry.chat_count.set(cur + 1);
+        self.emit_room_event(room_id, &GameEvent::ChatPosted { room_id, message: msg }).await;
+    }
+
+    async fn emit_room_event(&mut self, room_id: u32, event: &GameEvent) {
+        self.runtime.emit(events::room_stream(room_id), event);
+        let entry = self.state.rooms.load_entry_mut(&room_id).await.unwrap();
+        entry.events.push(event.clone());
+    }
+
+    async fn emit_lobby_event(&mut self, event: &GameEvent) {
+        self.runtime.emit(events::lobby_stream(), event);
+        self.state.lobby_events.push(event.clone());
+    }
+
+    async fn emit_tournament_progress(&mut self, tournament_id: u64) {
+        let Some(status) = self.state.tournament_status.get(&tournament_id).await.unwrap() else {
+            return;
+        };
+        let bracket = self
+            .state
+            .tournament_brackets
+            .get(&tournament_id)
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        self.emit_lobby_event(&GameEvent::TournamentAdvanced {
+            tournament_id,
+            status,
+            matches_played: bracket.iter().filter(|m| m.result.is_some()).count() as u32,
+            matches_total: bracket.len() as u32,
+        })
+        .await;
+    }
+
+    async fn get_game_state_response(&self, room_id: u32, room: &Room) -> GameStateResponse {
+        let players = room.players;
+        let time_remaining = room
+            .clock
+            .as_ref()
+            .map(|clock| clock.remaining_all(self.runtime.system_time().micros()));
+
+        let moves_history = self.current_game_moves(room_id, room).await;
+
+        let stats0 = match players[0] {
+            Some(p) => self.state.player_stats.get(&p).await.unwrap().unwrap_or((0, 0, 0)),
+            None => (0, 0, 0),
+        };
+        let stats1 = match players[1] {
+            Some(p) => self.state.player_stats.get(&p).await.unwrap().unwrap_or((0, 0, 0)),
+            None => (0, 0, 0),
+        };
+
+        GameStateResponse {
+            board: room.game.display_board(),
+            players,
+            current_player: players[room.game.current_player as usize],
+            winner: room.winner,
+            time_remaining,
+            player_stats: [stats0, stats1],
+            moves_history,
+            ultimate_board: room.game.ultimate_board().cloned(),
+        }
+    }
+
+    // bracket helpers (type-safe u64 math)
+    async fn generate_single_elimination_bracket(&self, players: &[AccountOwner]) -> Vec<BracketMatch> {
+        let n = players.len() as u64;
+        if n < 2 {
+            return vec![];
+        }
+
+        let mut matches: Vec<BracketMatch> = Vec::new();
+        let mut match_id: u64 = 1;
+
+        // first round
+        let mut i = 0usize;
+        while i < players.len() {
+            let p1 = Some(players[i]);
+            let p2 = if i + 1 < players.len() { Some(players[i + 1]) } else { None };
+
+            matches.push(BracketMatch {
+                match_id,
+                player1: p1,
+                player2: p2,
+                result: None,
+                next_match: None,
+                round: 1,
+            });
+
+            match_id += 1;
+            i += 2;
+        }
+
+        // (simple: no full tree wiring, but compiles clean)
+        let _ = n;
+        matches
+    }
+
+    async fn generate_swiss_pairings(&self, players: &[AccountOwner], rounds: u32) -> Vec<BracketMatch> {
+        let mut out = Vec::new();
+        let mut match_id: u64 = 1;
+
+        for round in 1..=rounds {
+            let mut i = 0usize;
+            while i + 1 < players.len() {
+                out.push(BracketMatch {
+                    match_id,
+                    player1: Some(players[i]),
+                    player2: Some(players[i + 1]),
+                    result: None,
+                    next_match: None,
+                    round,
+                });
+                match_id += 1;
+                i += 2;
+            }
+        }
+        out
+    }
+
+    async fn generate_round_robin_bracket(&self, players: &[AccountOwner]) -> Vec<BracketMatch> {
+        let mut out = Vec::new();
+        let mut match_id: u64 = 1;
+
+        for i in 0..players.len() {
+            for j in (i + 1)..players.len() {
+                out.push(BracketMatch {
+                    match_id,
+                    player1: Some(players[i]),
+                    player2: Some(players[j]),
+                    result: None,
+                    next_match: None,
+                    round: 1,
+                });
+                match_id += 1;
+            }
+        }
+        out
+    }
+
+    async fn check_tournament_completion(&mut self, tournament_id: u64) {
+        let Some(bracket) = self.state.tournament_brackets.get(&tournament_id).await.unwrap() else {
+            return;
+        };
+
+        let all_done = bracket.iter().all(|m| m.result.is_some());
+        if !all_done {
+            return;
+        }
+
+        let players = self
+            .state
+            .tournament_players
+            .get(&tournament_id)
+            .await
+            .unwrap()
+            .unwrap_or_default();
+
+        let winners = players.into_iter().take(PAID_PLACES).collect::<Vec<_>>();
+
+        let _ = self.state.tournament_winners.insert(&tournament_id, winners);
+        let _ = self
+            .state
+            .tournament_status
+            .insert(&tournament_id, TournamentStatus::Completed);
+    }
+}
+
+// ===================== SERVICE =====================
+
+pub struct InstantTacToeService {
+    state: Arc<InstantTacToeState>,
+    runtime: Arc<ServiceRuntime<Self>>,
+}
+
+impl WithServiceAbi for InstantTacToeService {
+    type Abi = abi::InstantTacToeAbi;
+}
+
+impl Service for InstantTacToeService {
+    type Parameters = ();
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = InstantTacToeState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load service state");
+        InstantTacToeService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    async fn handle_query(&self, request: Request) -> GraphQLResponse {
+        let query = QueryRoot {
+            state: self.state.clone(),
+            now: self.runtime.system_time().micros(),
+        };
+        let mutation = MutationRoot {
+            runtime: self.runtime.clone(),
+        };
+        Schema::build(query, mutation, EmptySubscription)
+            .finish()
+            .execute(request)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::{
+        util::BlockingWait,
+        views::{MapView, RegisterView, ViewStorageContext},
+    };
+
+    use super::*;
+    use crate::state::LegacyReplay;
+
+    type Contract = InstantTacToeContract;
+
+    /// The first release's root view, to write state the way it did.
+    #[derive(RootView)]
+    #[view(context = ViewStorageContext)]
+    struct FirstReleaseState {
+        next_room_id: RegisterView<u32>,
+        next_tournament_id: RegisterView<u64>,
+        next_guild_id: RegisterView<u64>,
+        room_names: MapView<u32, String>,
+        room_creators: MapView<u32, AccountOwner>,
+        room_passwords: MapView<u32, Option<String>>,
+        room_is_full: MapView<u32, bool>,
+        room_modes: MapView<u32, GameMode>,
+        room_stakes: MapView<u32, Option<u64>>,
+        game_boards: MapView<u32, [Option<char>; 9]>,
+        game_current_players: MapView<u32, u8>,
+        game_players: MapView<u32, [Option<AccountOwner>; 2]>,
+        game_winners: MapView<u32, Option<char>>,
+        game_move_times: MapView<u32, u64>,
+        game_moves_history: MapView<(u32, u32), u32>,
+        tournament_names: MapView<u64, String>,
+        tournament_formats: MapView<u64, TournamentFormat>,
+        tournament_status: MapView<u64, TournamentStatus>,
+        tournament_entry_fees: MapView<u64, Option<u64>>,
+        tournament_players: MapView<u64, Vec<AccountOwner>>,
+        tournament_brackets: MapView<u64, Vec<BracketMatch>>,
+        tournament_winners: MapView<u64, Vec<AccountOwner>>,
+        tournament_prize_pools: MapView<u64, u64>,
+        player_balances: MapView<AccountOwner, u64>,
+        player_elo: MapView<AccountOwner, u32>,
+        player_stats: MapView<AccountOwner, (u32, u32, u32)>,
+        player_streaks: MapView<AccountOwner, i32>,
+        player_achievements: MapView<(AccountOwner, u32), bool>,
+        staked_games: MapView<u32, StakedGame>,
+        escrow_accounts: MapView<AccountOwner, u64>,
+        guilds: MapView<u64, Guild>,
+        guild_members: MapView<(u64, AccountOwner), GuildRole>,
+        player_guilds: MapView<AccountOwner, u64>,
+        chat_messages: MapView<(u32, u64), ChatMessage>,
+        chat_counts: MapView<u32, u64>,
+        nicknames: MapView<AccountOwner, String>,
+        player_powerups: MapView<(AccountOwner, PowerUpType), u32>,
+        game_replays: MapView<u64, LegacyReplay>,
+        leaderboard: MapView<(GameMode, u32), AccountOwner>,
+    }
+
+    /// A mock chain holding what `write` stored through the first release's layout.
+    fn first_release_chain(write: impl FnOnce(&mut FirstReleaseState)) -> ContractRuntime<Contract> {
+        let runtime = ContractRuntime::new().with_application_parameters(());
+        let mut state = FirstReleaseState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+        write(&mut state);
+        state.save().blocking_wait().expect("Failed to write to mock key value store");
+        runtime
+    }
+
+    /// Loads the chain with the current layout and runs the migration to the
+    /// end; also returns how many operations it took.
+    fn upgrade(runtime: ContractRuntime<Contract>) -> (Contract, usize) {
+        let state = InstantTacToeState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+        let mut contract = InstantTacToeContract { state, runtime };
+        let mut operations = 1;
+        while !contract.migrate_storage().blocking_wait() {
+            operations += 1;
+        }
+        (contract, operations)
+    }
+
+    #[test]
+    fn first_release_move_history_moves_into_room_logs() {
+        let long_game = MIGRATION_BATCH as u32 + 5;
+        let runtime = first_release_chain(|old| {
+            old.next_room_id.set(2);
+            for move_num in 0..long_game {
+                old.game_moves_history.insert(&(0, move_num), move_num % 9).unwrap();
+            }
+            for (move_num, position) in [4, 0, 8].into_iter().enumerate() {
+                old.game_moves_history.insert(&(1, move_num as u32), position).unwrap();
+            }
+        });
+
+        let (contract, operations) = upgrade(runtime);
+        assert!(operations > 1, "the history doesn't fit in one batch");
+        assert_eq!(*contract.state.storage_version.get(), STORAGE_VERSION);
+        assert_eq!(*contract.state.next_room_id.get(), 2);
+        assert_eq!(*contract.state.next_replay_id.get(), 1);
+        assert!(contract.state.game_moves_history.indices().blocking_wait().unwrap().is_empty());
+
+        let room_moves = contract.state.room_moves.try_load_entry(&0).blocking_wait().unwrap().unwrap();
+        assert_eq!(*room_moves.count.get(), long_game);
+        assert_eq!(room_moves.positions.get(&10).blocking_wait().unwrap(), Some(1));
+
+        let room_moves = contract.state.room_moves.try_load_entry(&1).blocking_wait().unwrap().unwrap();
+        assert_eq!(*room_moves.count.get(), 3);
+        let positions: Vec<_> = (0..3)
+            .map(|move_num| room_moves.positions.get(&move_num).blocking_wait().unwrap())
+            .collect();
+        assert_eq!(positions, [Some(4), Some(0), Some(8)]);
+    }
+
+    #[test]
+    fn first_release_rooms_become_room_records() {
+        let alice = AccountOwner::Address20([1; 20]);
+        let bob = AccountOwner::Address20([2; 20]);
+        let x = Some('X');
+        let o = Some('O');
+        let won = [x, x, x, o, o, None, None, None, None];
+        let opening = [x, None, None, None, o, None, None, None, None];
+        let runtime = first_release_chain(|old| {
+            // (room_id, mode, full, players, board, current player, winner)
+            let rooms = [
+                (0, GameMode::Classic, true, [Some(alice), Some(bob)], won, 1, x),
+                (1, GameMode::Speed(30), false, [Some(alice), None], [None; 9], 0, None),
+                (2, GameMode::Classic, true, [Some(alice), Some(bob)], opening, 0, None),
+                (3, GameMode::Classic, true, [None, Some(bob)], [None; 9], 0, None),
+                (4, GameMode::Classic, false, [None, None], [None; 9], 0, None),
+            ];
+            for (room_id, mode, full, players, board, current, winner) in rooms {
+                old.room_names.insert(&room_id, format!("room {}", room_id)).unwrap();
+                old.room_creators.insert(&room_id, alice).unwrap();
+                old.room_passwords.insert(&room_id, None).unwrap();
+                old.room_is_full.insert(&room_id, full).unwrap();
+                old.room_modes.insert(&room_id, mode).unwrap();
+                old.room_stakes.insert(&room_id, None).unwrap();
+                old.game_boards.insert(&room_id, board).unwrap();
+                old.game_current_players.insert(&room_id, current).unwrap();
+                old.game_players.insert(&room_id, players).unwrap();
+                old.game_winners.insert(&room_id, winner).unwrap();
+                old.game_move_times.insert(&room_id, 1_000).unwrap();
+            }
+            old.next_room_id.set(5);
+            for (index, text) in ["hi", "gg"].into_iter().enumerate() {
+                let message = ChatMessage {
+                    sender: "alice".to_string(),
+                    text: text.to_string(),
+                    timestamp: index as u64,
+                };
+                old.chat_messages.insert(&(0, index as u64), message).unwrap();
+            }
+            old.chat_counts.insert(&0, 2).unwrap();
+        });
+
+        let (contract, _) = upgrade(runtime);
+        let room = |room_id| contract.load_room(room_id).blocking_wait().unwrap();
+
+        let finished = room(0);
+        assert_eq!(finished.name, "room 0");
+        assert_eq!(finished.status, RoomStatus::Finished);
+        assert_eq!(finished.winner, x);
+        assert_eq!(finished.game.display_board()[..3], [x, x, x]);
+
+        let waiting = room(1);
+        assert_eq!(waiting.status, RoomStatus::WaitingForOpponent);
+        assert!(waiting.clock.is_some_and(|clock| clock.running.is_none()));
+
+        let in_progress = room(2);
+        assert_eq!(in_progress.status, RoomStatus::InProgress);
+        assert_eq!(in_progress.players, [Some(alice), Some(bob)]);
+        assert_eq!(in_progress.game.to_move(), 'X');
+        assert_eq!(in_progress.game.legal_moves().len(), 7);
+
+        assert_eq!(room(3).status, RoomStatus::Abandoned);
+        assert_eq!(room(4).status, RoomStatus::Closed);
+
+        let entry = contract.state.rooms.try_load_entry(&0).blocking_wait().unwrap().unwrap();
+        assert_eq!(*entry.chat_count.get(), 2);
+        assert_eq!(entry.chat.get(&1).blocking_wait().unwrap().map(|message| message.text), Some("gg".to_string()));
+
+        assert!(contract.state.room_names.indices().blocking_wait().unwrap().is_empty());
+        assert!(contract.state.game_boards.indices().blocking_wait().unwrap().is_empty());
+        assert!(contract.state.chat_messages.indices().blocking_wait().unwrap().is_empty());
+        assert!(contract.state.chat_counts.indices().blocking_wait().unwrap().is_empty());
+    }
+
+    #[test]
+    fn first_release_money_is_not_carried_over() {
+        let alice = AccountOwner::Address20([1; 20]);
+        let runtime = first_release_chain(|old| {
+            old.player_balances.insert(&alice, 1_000).unwrap();
+            old.escrow_accounts.insert(&alice, 50).unwrap();
+            old.tournament_prize_pools.insert(&0, 200).unwrap();
+            old.room_names.insert(&0, "staked".to_string()).unwrap();
+            old.room_creators.insert(&0, alice).unwrap();
+            old.room_stakes.insert(&0, Some(50)).unwrap();
+            old.game_players.insert(&0, [Some(alice), None]).unwrap();
+            let staked_game = StakedGame {
+                room_id: 0,
+                total_pot: 100,
+                players_stake: [50, 0],
+                claimed: [false, false],
+            };
+            old.staked_games.insert(&0, staked_game).unwrap();
+            old.player_elo.insert(&alice, 1_600).unwrap();
+        });
+
+        let (contract, _) = upgrade(runtime);
+        let state = &contract.state;
+        assert_eq!(state.player_balances.get(&alice).blocking_wait().unwrap(), None);
+        assert_eq!(state.escrow_accounts.get(&alice).blocking_wait().unwrap(), None);
+        assert_eq!(state.tournament_prize_pools.get(&0).blocking_wait().unwrap(), None);
+        assert!(state.staked_games.get(&0).blocking_wait().unwrap().is_none());
+        assert_eq!(contract.load_room(0).blocking_wait().unwrap().stake, None);
+        // ratings aren't money and stay
+        assert_eq!(state.player_elo.get(&alice).blocking_wait().unwrap(), Some(1_600));
+    }
+
+    #[test]
+    fn first_release_replays_are_dropped() {
+        let alice = AccountOwner::Address20([1; 20]);
+        let runtime = first_release_chain(|old| {
+            let replay = LegacyReplay {
+                room_id: 0,
+                moves: vec![0, 4, 1],
+                players: [Some(alice), None],
+                winner: None,
+                duration: 30,
+                timestamp: 0,
+            };
+            old.game_replays.insert(&0, replay).unwrap();
+        });
+
+        let (contract, _) = upgrade(runtime);
+        assert!(contract.state.legacy_replays.indices().blocking_wait().unwrap().is_empty());
+        assert_eq!(*contract.state.next_replay_id.get(), 1);
+        assert!(contract.state.game_replays.get(&0).blocking_wait().unwrap().is_none());
+    }
+
+    #[test]
+    fn prize_distribution_must_add_up_to_100() {
+        assert_eq!(Contract::validate_prize_distribution(&[50, 30, 20]), Ok(()));
+        assert_eq!(Contract::validate_prize_distribution(&[100]), Ok(()));
+        assert_eq!(
+            Contract::validate_prize_distribution(&[50, 30]),
+            Err(GameError::InvalidPrizeDistribution { total: 80 })
+        );
+        assert_eq!(
+            Contract::validate_prize_distribution(&[]),
+            Err(GameError::InvalidPrizeDistribution { total: 0 })
+        );
+        assert_eq!(
+            Contract::validate_prize_distribution(&[40, 30, 20, 10]),
+            Err(GameError::TooManyPrizePlaces { max: 3 })
+        );
+    }
+
+    #[test]
+    fn wrapping_prize_distribution_is_rejected() {
+        // wraps to exactly 100 with a plain u32 sum
+        let distribution = [u32::MAX, 101];
+        assert_eq!(distribution[0].wrapping_add(distribution[1]), 100);
+        assert!(matches!(
+            Contract::validate_prize_distribution(&distribution),
+            Err(GameError::InvalidPrizeDistribution { .. })
+        ));
+        assert!(Contract::validate_prize_distribution(&[101, 0]).is_err());
+    }
+
+    #[test]
+    fn prize_shares_never_exceed_the_pool() {
+        assert_eq!(Contract::prize_shares(1000, &[50, 30, 20], 3), vec![500, 300, 200]);
+        // rounding remainder and unfilled places go to first place
+        assert_eq!(Contract::prize_shares(1001, &[50, 30, 20], 3), vec![501, 300, 200]);
+        assert_eq!(Contract::prize_shares(1000, &[50, 30, 20], 2), vec![700, 300]);
+
+        for distribution in [vec![u32::MAX, 101], vec![0, u32::MAX], vec![100, 100, 100]] {
+            let shares = Contract::prize_shares(1000, &distribution, 3);
+            assert_eq!(shares.iter().sum::<u64>(), 1000, "{:?}", distribution);
+        }
+    }
+}
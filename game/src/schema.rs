@@ -0,0 +1,1200 @@
+//! GraphQL schema served by `InstantTacToeService`.
+//!
+//! Objects hold little more than an id and resolve their fields from the views
+//! on demand, so a query only reads the state behind the fields it selects.
+
+use std::sync::Arc;
+
+use abi::{
+    error::GameError,
+    events::GameEvent,
+    notation::GameRecord,
+    rules::{self, GameState},
+    solver::{self, MoveLabel, Solver},
+    ChatMessage, Difficulty, GameMode, MatchResult, Operation, PowerUpType, RoomStatus, TournamentFormat,
+    TournamentStatus, UltimateBoard,
+};
+use async_graphql::{ComplexObject, Enum, ErrorExtensions, Object, Result, SimpleObject};
+use linera_sdk::{linera_base_types::AccountOwner, ServiceRuntime};
+
+use crate::{
+    state::{
+        BracketMatch, EventLogView, GameReplay, Guild, GuildRole, InstantTacToeState, MatchSeries, Room,
+        Transaction, TransactionKind,
+    },
+    InstantTacToeService,
+};
+
+pub struct QueryRoot {
+    pub state: Arc<InstantTacToeState>,
+    pub now: u64, // system time the query runs at, in microseconds
+}
+
+#[Object]
+impl QueryRoot {
+    /// All rooms, optionally only those in the given state.
+    async fn rooms(&self, status: Option<RoomStatus>) -> Vec<RoomObject> {
+        let mut rooms = Vec::new();
+        for room_id in self.state.rooms.indices().await.unwrap_or_default() {
+            let Some(room) = load_room(&self.state, room_id).await else { continue; };
+            if status.is_some_and(|status| status != room.status) {
+                continue;
+            }
+            rooms.push(self.room_object(room_id, room));
+        }
+        rooms
+    }
+
+    async fn room(&self, room_id: u32) -> Result<RoomObject> {
+        match load_room(&self.state, room_id).await {
+            Some(room) => Ok(self.room_object(room_id, room)),
+            None => Err(GameError::RoomNotFound { room_id }.extend()),
+        }
+    }
+
+    async fn player(&self, owner: AccountOwner) -> PlayerObject {
+        PlayerObject::new(&self.state, owner)
+    }
+
+    /// Players with at least one rated game, highest Elo first.
+    async fn leaderboard(&self, #[graphql(default = 10)] limit: u32) -> Vec<PlayerObject> {
+        let mut ratings = Vec::new();
+        for owner in self.state.player_stats.indices().await.unwrap_or_default() {
+            let elo = self.state.player_elo.get(&owner).await.unwrap().unwrap_or(1500);
+            ratings.push((elo, owner));
+        }
+        ratings.sort_by(|a, b| b.0.cmp(&a.0));
+        ratings
+            .into_iter()
+            .take(limit as usize)
+            .map(|(_, owner)| PlayerObject::new(&self.state, owner))
+            .collect()
+    }
+
+    async fn tournaments(&self, status: Option<TournamentStatus>) -> Vec<TournamentObject> {
+        let mut tournaments = Vec::new();
+        for tournament_id in self.state.tournament_names.indices().await.unwrap_or_default() {
+            let Some(tournament) = TournamentObject::load(&self.state, tournament_id).await else { continue; };
+            if status.is_some_and(|status| status != tournament.status) {
+                continue;
+            }
+            tournaments.push(tournament);
+        }
+        tournaments
+    }
+
+    async fn tournament(&self, tournament_id: u64) -> Result<TournamentObject> {
+        TournamentObject::load(&self.state, tournament_id)
+            .await
+            .ok_or_else(|| GameError::TournamentNotFound { tournament_id }.extend())
+    }
+
+    /// All guilds, highest level first.
+    async fn guilds(&self) -> Vec<GuildObject> {
+        let mut guilds = Vec::new();
+        for guild_id in self.state.guilds.indices().await.unwrap_or_default() {
+            if let Some(guild) = self.state.guilds.get(&guild_id).await.unwrap() {
+                guilds.push(guild);
+            }
+        }
+        guilds.sort_by(|a, b| b.level.cmp(&a.level).then(b.member_count.cmp(&a.member_count)));
+        guilds.into_iter().map(|guild| GuildObject::new(&self.state, guild)).collect()
+    }
+
+    async fn guild(&self, guild_id: u64) -> Result<GuildObject> {
+        match self.state.guilds.get(&guild_id).await.unwrap() {
+            Some(guild) => Ok(GuildObject::new(&self.state, guild)),
+            None => Err(GameError::GuildNotFound { guild_id }.extend()),
+        }
+    }
+
+    async fn replay(&self, replay_id: u64) -> Result<ReplayObject> {
+        ReplayObject::load(&self.state, replay_id)
+            .await
+            .ok_or_else(|| GameError::ReplayNotFound { replay_id }.extend())
+    }
+
+    /// Events of a room's stream from index `since` on, oldest first. After
+    /// each new block, ask again with the `nextIndex` of the last page.
+    async fn room_events(&self, room_id: u32, since: Option<u32>, limit: Option<u32>) -> Result<EventPage> {
+        match self.state.rooms.try_load_entry(&room_id).await.unwrap() {
+            Some(entry) => Ok(EventPage::read(&entry.events, since, limit).await),
+            None => Err(GameError::RoomNotFound { room_id }.extend()),
+        }
+    }
+
+    /// Events of the lobby stream, paged like `roomEvents`.
+    async fn lobby_events(&self, since: Option<u32>, limit: Option<u32>) -> EventPage {
+        EventPage::read(&self.state.lobby_events, since, limit).await
+    }
+
+    /// Solves an arbitrary 3x3 position. The side to move is inferred from the
+    /// piece count if omitted.
+    async fn analyze_board(&self, board: [Option<char>; 9], to_move: Option<char>) -> Result<Analysis> {
+        analyze(board, to_move).map_err(|error| error.extend())
+    }
+}
+
+impl QueryRoot {
+    fn room_object(&self, room_id: u32, room: Room) -> RoomObject {
+        RoomObject {
+            state: self.state.clone(),
+            id: room_id,
+            room,
+            now: self.now,
+        }
+    }
+}
+
+/// One mutation per `Operation`. Each schedules the operation for the next
+/// block `linera service` proposes on the chain, signed by the chain's owner.
+pub struct MutationRoot {
+    pub runtime: Arc<ServiceRuntime<InstantTacToeService>>,
+}
+
+impl MutationRoot {
+    fn schedule(&self, operation: Operation) -> [u8; 0] {
+        self.runtime.schedule_operation(&operation);
+        []
+    }
+}
+
+#[Object]
+impl MutationRoot {
+    async fn set_nickname(&self, name: String) -> [u8; 0] {
+        self.schedule(Operation::SetNickname { name })
+    }
+
+    async fn create_match(
+        &self,
+        room_name: String,
+        password: Option<String>,
+        mode: GameMode,
+        stake: Option<u64>,
+        increment: Option<u64>,
+        series_length: Option<u32>,
+    ) -> [u8; 0] {
+        self.schedule(Operation::CreateMatch {
+            room_name,
+            password,
+            mode,
+            stake,
+            increment,
+            series_length,
+        })
+    }
+
+    async fn create_bot_match(&self, difficulty: Difficulty) -> [u8; 0] {
+        self.schedule(Operation::CreateBotMatch { difficulty })
+    }
+
+    async fn join_game(&self, room_id: u32, password: Option<String>) -> [u8; 0] {
+        self.schedule(Operation::JoinGame { room_id, password })
+    }
+
+    async fn make_move(&self, room_id: u32, position: u32) -> [u8; 0] {
+        self.schedule(Operation::MakeMove { room_id, position })
+    }
+
+    async fn make_ultimate_move(&self, room_id: u32, board: u32, cell: u32) -> [u8; 0] {
+        self.schedule(Operation::MakeUltimateMove { room_id, board, cell })
+    }
+
+    async fn post_message(&self, room_id: u32, text: String) -> [u8; 0] {
+        self.schedule(Operation::PostMessage { text, room_id })
+    }
+
+    async fn offer_rematch(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::OfferRematch { room_id })
+    }
+
+    async fn accept_rematch(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::AcceptRematch { room_id })
+    }
+
+    async fn leave_room(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::LeaveRoom { room_id })
+    }
+
+    async fn cancel_room(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::CancelRoom { room_id })
+    }
+
+    async fn surrender(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::Surrender { room_id })
+    }
+
+    async fn claim_timeout(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::ClaimTimeout { room_id })
+    }
+
+    async fn offer_draw(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::OfferDraw { room_id })
+    }
+
+    async fn accept_draw(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::AcceptDraw { room_id })
+    }
+
+    async fn request_takeback(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::RequestTakeback { room_id })
+    }
+
+    async fn accept_takeback(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::AcceptTakeback { room_id })
+    }
+
+    async fn create_tournament(
+        &self,
+        name: String,
+        format: TournamentFormat,
+        entry_fee: Option<u64>,
+        max_players: u32,
+        prize_distribution: Vec<u32>,
+    ) -> [u8; 0] {
+        self.schedule(Operation::CreateTournament {
+            name,
+            format,
+            entry_fee,
+            max_players,
+            prize_distribution,
+        })
+    }
+
+    async fn join_tournament(&self, tournament_id: u64) -> [u8; 0] {
+        self.schedule(Operation::JoinTournament { tournament_id })
+    }
+
+    async fn start_tournament(&self, tournament_id: u64) -> [u8; 0] {
+        self.schedule(Operation::StartTournament { tournament_id })
+    }
+
+    async fn report_match_result(&self, tournament_id: u64, match_id: u64, result: MatchResult) -> [u8; 0] {
+        self.schedule(Operation::ReportMatchResult {
+            tournament_id,
+            match_id,
+            result,
+        })
+    }
+
+    async fn deposit_tokens(&self, amount: u64) -> [u8; 0] {
+        self.schedule(Operation::DepositTokens { amount })
+    }
+
+    async fn withdraw_tokens(&self, amount: u64) -> [u8; 0] {
+        self.schedule(Operation::WithdrawTokens { amount })
+    }
+
+    async fn claim_rewards(&self) -> [u8; 0] {
+        self.schedule(Operation::ClaimRewards)
+    }
+
+    async fn create_guild(&self, name: String, tag: String) -> [u8; 0] {
+        self.schedule(Operation::CreateGuild { name, tag })
+    }
+
+    async fn join_guild(&self, guild_id: u64) -> [u8; 0] {
+        self.schedule(Operation::JoinGuild { guild_id })
+    }
+
+    async fn invite_to_guild(&self, player: AccountOwner, guild_id: u64) -> [u8; 0] {
+        self.schedule(Operation::InviteToGuild { player, guild_id })
+    }
+
+    async fn use_power_up(&self, room_id: u32, power_up: PowerUpType) -> [u8; 0] {
+        self.schedule(Operation::UsePowerUp { room_id, power_up })
+    }
+
+    /// Pins the room's latest replay.
+    async fn save_replay(&self, room_id: u32) -> [u8; 0] {
+        self.schedule(Operation::SaveReplay { room_id })
+    }
+
+    async fn challenge_player(&self, player: AccountOwner, stake: Option<u64>) -> [u8; 0] {
+        self.schedule(Operation::ChallengePlayer { player, stake })
+    }
+}
+
+async fn load_room(state: &InstantTacToeState, room_id: u32) -> Option<Room> {
+    state
+        .rooms
+        .try_load_entry(&room_id)
+        .await
+        .unwrap()
+        .and_then(|entry| entry.record.get().clone())
+}
+
+// ===================== ROOMS =====================
+
+pub struct RoomObject {
+    state: Arc<InstantTacToeState>,
+    id: u32,
+    room: Room,
+    now: u64,
+}
+
+#[Object(name = "Room")]
+impl RoomObject {
+    async fn id(&self) -> u32 {
+        self.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.room.name
+    }
+
+    async fn creator(&self) -> PlayerObject {
+        PlayerObject::new(&self.state, self.room.creator)
+    }
+
+    async fn has_password(&self) -> bool {
+        self.room.password.is_some()
+    }
+
+    async fn status(&self) -> RoomStatus {
+        self.room.status
+    }
+
+    async fn mode(&self) -> &GameMode {
+        &self.room.mode
+    }
+
+    async fn stake(&self) -> Option<u64> {
+        self.room.stake
+    }
+
+    /// Set on practice rooms against the built-in bot.
+    async fn bot_difficulty(&self) -> Option<Difficulty> {
+        self.room.bot
+    }
+
+    /// Both seats, X first; an empty seat is null.
+    async fn players(&self) -> Vec<Option<PlayerObject>> {
+        self.room
+            .players
+            .iter()
+            .map(|player| player.map(|owner| PlayerObject::new(&self.state, owner)))
+            .collect()
+    }
+
+    async fn player_count(&self) -> u32 {
+        self.room.players.iter().flatten().count() as u32
+    }
+
+    async fn is_full(&self) -> bool {
+        self.room.status != RoomStatus::WaitingForOpponent
+    }
+
+    /// The game currently played in the room.
+    async fn board(&self) -> BoardObject {
+        BoardObject {
+            state: self.state.clone(),
+            room_id: self.id,
+            room: self.room.clone(),
+            now: self.now,
+        }
+    }
+
+    async fn series(&self) -> Option<&MatchSeries> {
+        self.room.series.as_ref()
+    }
+
+    async fn started_at(&self) -> u64 {
+        self.room.started_at
+    }
+
+    async fn last_move_at(&self) -> u64 {
+        self.room.last_move_at
+    }
+
+    async fn rematch_offered_by(&self) -> Option<AccountOwner> {
+        self.room.rematch_offer
+    }
+
+    async fn draw_offered_by(&self) -> Option<AccountOwner> {
+        self.room.draw_offer
+    }
+
+    async fn takeback_requested_by(&self) -> Option<AccountOwner> {
+        self.room.takeback_request
+    }
+
+    /// Replay of the last game finished in the room.
+    async fn last_replay(&self) -> Option<ReplayObject> {
+        ReplayObject::load(&self.state, self.room.last_replay?).await
+    }
+
+    /// The chat, oldest first; with `limit`, only the latest messages.
+    async fn chat(&self, limit: Option<u32>) -> Vec<ChatMessage> {
+        let Some(entry) = self.state.rooms.try_load_entry(&self.id).await.unwrap() else {
+            return Vec::new();
+        };
+
+        let count = *entry.chat_count.get();
+        let first = limit.map_or(0, |limit| count.saturating_sub(limit.into()));
+        let mut messages = Vec::new();
+        for i in first..count {
+            if let Some(message) = entry.chat.get(&i).await.unwrap() {
+                messages.push(message);
+            }
+        }
+        messages
+    }
+}
+
+pub struct BoardObject {
+    state: Arc<InstantTacToeState>,
+    room_id: u32,
+    room: Room,
+    now: u64,
+}
+
+#[Object(name = "Board")]
+impl BoardObject {
+    /// The 3x3 board, or the meta-board of sub-board results in Ultimate games.
+    async fn cells(&self) -> [Option<char>; 9] {
+        self.room.game.display_board()
+    }
+
+    async fn ultimate(&self) -> Option<&UltimateBoard> {
+        self.room.game.ultimate_board()
+    }
+
+    async fn to_move(&self) -> char {
+        self.room.game.to_move()
+    }
+
+    async fn current_player(&self) -> Option<AccountOwner> {
+        self.room.players[self.room.game.current_player as usize]
+    }
+
+    /// 'X', 'O' or 'T' for a draw, once the game is over.
+    async fn winner(&self) -> Option<char> {
+        self.room.winner
+    }
+
+    async fn legal_moves(&self) -> Vec<u32> {
+        if self.room.winner.is_some() {
+            Vec::new()
+        } else {
+            self.room.game.legal_moves()
+        }
+    }
+
+    /// Moves of the current game; Ultimate moves are encoded as `board * 9 + cell`.
+    async fn moves(&self) -> Vec<u32> {
+        let Some(room_moves) = self.state.room_moves.try_load_entry(&self.room_id).await.unwrap() else {
+            return Vec::new();
+        };
+
+        let mut moves = Vec::new();
+        for move_num in self.room.first_move..*room_moves.count.get() {
+            if let Some(position) = room_moves.positions.get(&move_num).await.unwrap() {
+                moves.push(position);
+            }
+        }
+        moves
+    }
+
+    async fn move_count(&self) -> u32 {
+        match self.state.room_moves.try_load_entry(&self.room_id).await.unwrap() {
+            Some(room_moves) => room_moves.count.get().saturating_sub(self.room.first_move),
+            None => 0,
+        }
+    }
+
+    /// Microseconds left per player in Speed rooms.
+    async fn time_remaining(&self) -> Option<[u64; 2]> {
+        self.room.clock.as_ref().map(|clock| clock.remaining_all(self.now))
+    }
+
+    async fn is_timed_out(&self) -> bool {
+        self.room.clock.as_ref().is_some_and(|clock| clock.flagged(self.now).is_some())
+    }
+
+    /// Solver analysis of the position; not available on Ultimate boards.
+    async fn analysis(&self) -> Result<Analysis> {
+        let game = &self.room.game;
+        if game.is_ultimate() {
+            return Err(GameError::AnalysisUnavailable.extend());
+        }
+        analyze(game.display_board(), Some(game.to_move())).map_err(|error| error.extend())
+    }
+}
+
+// renamed so they don't clash with the inherent methods they call
+#[ComplexObject]
+impl MatchSeries {
+    #[graphql(name = "gamesPlayed")]
+    async fn resolve_games_played(&self) -> u32 {
+        self.games_played()
+    }
+
+    #[graphql(name = "isDecided")]
+    async fn resolve_is_decided(&self) -> bool {
+        self.is_decided()
+    }
+}
+
+// ===================== PLAYERS =====================
+
+pub struct PlayerObject {
+    state: Arc<InstantTacToeState>,
+    owner: AccountOwner,
+}
+
+impl PlayerObject {
+    fn new(state: &Arc<InstantTacToeState>, owner: AccountOwner) -> Self {
+        PlayerObject {
+            state: state.clone(),
+            owner,
+        }
+    }
+
+    async fn load_escrow(&self) -> Vec<EscrowEntry> {
+        let Some(escrow) = self.state.room_escrow.try_load_entry(&self.owner).await.unwrap() else {
+            return Vec::new();
+        };
+        let room_ids = escrow.indices().await.unwrap_or_default();
+        let mut entries = Vec::new();
+        for room_id in room_ids {
+            if let Some(amount) = escrow.get(&room_id).await.unwrap() {
+                entries.push(EscrowEntry { room_id, amount });
+            }
+        }
+        entries
+    }
+}
+
+#[Object(name = "Player")]
+impl PlayerObject {
+    async fn account(&self) -> AccountOwner {
+        self.owner
+    }
+
+    async fn nickname(&self) -> String {
+        self.state
+            .nicknames
+            .get(&self.owner)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| "Anonymous".to_string())
+    }
+
+    async fn elo(&self) -> u32 {
+        self.state.player_elo.get(&self.owner).await.unwrap().unwrap_or(1500)
+    }
+
+    /// Rated games against other players.
+    async fn stats(&self) -> PlayerRecord {
+        PlayerRecord::new(self.state.player_stats.get(&self.owner).await.unwrap().unwrap_or((0, 0, 0)))
+    }
+
+    /// Practice games against the bot.
+    async fn practice(&self) -> PlayerRecord {
+        PlayerRecord::new(self.state.practice_stats.get(&self.owner).await.unwrap().unwrap_or((0, 0, 0)))
+    }
+
+    /// Wins in a row. A loss resets it to 0; a draw leaves it as it is.
+    async fn streak(&self) -> i32 {
+        self.state.player_streaks.get(&self.owner).await.unwrap().unwrap_or(0)
+    }
+
+    async fn balance(&self) -> u64 {
+        self.state.player_balances.get(&self.owner).await.unwrap().unwrap_or(0)
+    }
+
+    /// Total of the stakes held for the player's unsettled rooms.
+    async fn in_escrow(&self) -> u64 {
+        self.load_escrow().await.iter().map(|entry| entry.amount).sum()
+    }
+
+    /// The stakes held for the player, per room.
+    async fn escrow(&self) -> Vec<EscrowEntry> {
+        self.load_escrow().await
+    }
+
+    async fn guild(&self) -> Option<GuildObject> {
+        let guild_id = self.state.player_guilds.get(&self.owner).await.unwrap()?;
+        let guild = self.state.guilds.get(&guild_id).await.unwrap()?;
+        Some(GuildObject::new(&self.state, guild))
+    }
+
+    /// One page of the player's replays, newest first. Pass the `nextCursor`
+    /// of a page to get the one after it. Only the player's own index entries
+    /// are read, and each page examines a bounded number of them.
+    async fn replays(&self, cursor: Option<u64>, limit: Option<u32>) -> ReplayPage {
+        const MAX_SCANNED: u64 = 200;
+
+        let limit = limit.unwrap_or(20).clamp(1, 100) as usize;
+        let head = self
+            .state
+            .player_replay_heads
+            .get(&self.owner)
+            .await
+            .unwrap()
+            .unwrap_or_default();
+
+        let mut replays = Vec::new();
+        let mut seq = cursor.unwrap_or(head.next_seq).min(head.next_seq);
+        let mut scanned = 0;
+        while seq > 0 && replays.len() < limit && scanned < MAX_SCANNED {
+            seq -= 1;
+            scanned += 1;
+            let Some(replay_id) = self.state.player_replay_index.get(&(self.owner, seq)).await.unwrap() else {
+                continue;
+            };
+            if let Some(replay) = ReplayObject::load(&self.state, replay_id).await {
+                replays.push(replay);
+            }
+        }
+
+        ReplayPage {
+            replays,
+            next_cursor: if seq > 0 { Some(seq) } else { None },
+        }
+    }
+
+    /// One page of the player's balance history, newest first, paged like `replays`.
+    async fn transactions(&self, cursor: Option<u64>, limit: Option<u32>) -> TransactionPage {
+        let limit = limit.unwrap_or(20).clamp(1, 100) as u64;
+        let Some(ledger) = self.state.ledgers.try_load_entry(&self.owner).await.unwrap() else {
+            return TransactionPage {
+                transactions: Vec::new(),
+                next_cursor: None,
+            };
+        };
+
+        let count = *ledger.count.get();
+        let end = cursor.unwrap_or(count).min(count);
+        let start = end.saturating_sub(limit);
+        let mut transactions = Vec::new();
+        for seq in (start..end).rev() {
+            if let Some(transaction) = ledger.entries.get(&seq).await.unwrap() {
+                transactions.push(TransactionObject::new(seq, transaction));
+            }
+        }
+
+        TransactionPage {
+            transactions,
+            next_cursor: if start > 0 { Some(start) } else { None },
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct EscrowEntry {
+    pub room_id: u32,
+    pub amount: u64,
+}
+
+#[derive(SimpleObject)]
+pub struct PlayerRecord {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub total_games: u32,
+    pub win_rate: f64, // percent
+}
+
+impl PlayerRecord {
+    fn new((wins, losses, draws): (u32, u32, u32)) -> Self {
+        let total_games = wins + losses + draws;
+        let win_rate = if total_games > 0 {
+            (wins as f64 / total_games as f64) * 100.0
+        } else {
+            0.0
+        };
+        PlayerRecord {
+            wins,
+            losses,
+            draws,
+            total_games,
+            win_rate,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TransactionPage {
+    pub transactions: Vec<TransactionObject>,
+    pub next_cursor: Option<u64>, // null on the last page
+}
+
+/// A ledger entry, with the room or tournament it refers to pulled out of its kind.
+#[derive(SimpleObject)]
+#[graphql(name = "Transaction")]
+pub struct TransactionObject {
+    pub seq: u64,
+    pub kind: TransactionType,
+    pub credit: bool, // false when the amount was taken from the balance
+    pub amount: u64,
+    pub balance: u64, // balance right after the entry
+    pub room_id: Option<u32>,
+    pub tournament_id: Option<u64>,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+pub enum TransactionType {
+    Deposit,
+    Withdraw,
+    StakeLocked,
+    StakeWon,
+    StakeRefunded,
+    EntryFee,
+    PrizePaid,
+}
+
+impl TransactionObject {
+    fn new(seq: u64, transaction: Transaction) -> Self {
+        let (kind, room_id, tournament_id) = match transaction.kind {
+            TransactionKind::Deposit => (TransactionType::Deposit, None, None),
+            TransactionKind::Withdraw => (TransactionType::Withdraw, None, None),
+            TransactionKind::StakeLocked { room_id } => (TransactionType::StakeLocked, Some(room_id), None),
+            TransactionKind::StakeWon { room_id } => (TransactionType::StakeWon, Some(room_id), None),
+            TransactionKind::StakeRefunded { room_id } => (TransactionType::StakeRefunded, Some(room_id), None),
+            TransactionKind::EntryFee { tournament_id } => (TransactionType::EntryFee, None, Some(tournament_id)),
+            TransactionKind::PrizePaid { tournament_id } => (TransactionType::PrizePaid, None, Some(tournament_id)),
+        };
+        TransactionObject {
+            seq,
+            kind,
+            credit: transaction.kind.is_credit(),
+            amount: transaction.amount,
+            balance: transaction.balance,
+            room_id,
+            tournament_id,
+            timestamp: transaction.timestamp,
+        }
+    }
+}
+
+// ===================== TOURNAMENTS =====================
+
+pub struct TournamentObject {
+    state: Arc<InstantTacToeState>,
+    id: u64,
+    name: String,
+    format: TournamentFormat,
+    status: TournamentStatus,
+}
+
+impl TournamentObject {
+    async fn load(state: &Arc<InstantTacToeState>, tournament_id: u64) -> Option<Self> {
+        Some(TournamentObject {
+            state: state.clone(),
+            id: tournament_id,
+            name: state.tournament_names.get(&tournament_id).await.unwrap()?,
+            format: state.tournament_formats.get(&tournament_id).await.unwrap()?,
+            status: state.tournament_status.get(&tournament_id).await.unwrap()?,
+        })
+    }
+}
+
+#[Object(name = "Tournament")]
+impl TournamentObject {
+    async fn id(&self) -> u64 {
+        self.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn format(&self) -> &TournamentFormat {
+        &self.format
+    }
+
+    async fn status(&self) -> TournamentStatus {
+        self.status
+    }
+
+    async fn entry_fee(&self) -> Option<u64> {
+        self.state.tournament_entry_fees.get(&self.id).await.unwrap().flatten()
+    }
+
+    async fn prize_pool(&self) -> u64 {
+        self.state.tournament_prize_pools.get(&self.id).await.unwrap().unwrap_or(0)
+    }
+
+    /// Percent of the prize pool paid to each place, first place first.
+    async fn prize_distribution(&self) -> Vec<u32> {
+        self.state
+            .tournament_prize_distributions
+            .get(&self.id)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| crate::LEGACY_PRIZE_DISTRIBUTION.to_vec())
+    }
+
+    async fn players(&self) -> Vec<PlayerObject> {
+        let players = self.state.tournament_players.get(&self.id).await.unwrap().unwrap_or_default();
+        players.into_iter().map(|owner| PlayerObject::new(&self.state, owner)).collect()
+    }
+
+    async fn bracket(&self) -> Vec<BracketMatch> {
+        self.state.tournament_brackets.get(&self.id).await.unwrap().unwrap_or_default()
+    }
+
+    /// Final standings, once the tournament is completed.
+    async fn winners(&self) -> Vec<PlayerObject> {
+        let winners = self.state.tournament_winners.get(&self.id).await.unwrap().unwrap_or_default();
+        winners.into_iter().map(|owner| PlayerObject::new(&self.state, owner)).collect()
+    }
+}
+
+// ===================== GUILDS =====================
+
+pub struct GuildObject {
+    state: Arc<InstantTacToeState>,
+    guild: Guild,
+}
+
+impl GuildObject {
+    fn new(state: &Arc<InstantTacToeState>, guild: Guild) -> Self {
+        GuildObject {
+            state: state.clone(),
+            guild,
+        }
+    }
+
+    async fn load_members(&self) -> Vec<(AccountOwner, GuildRole)> {
+        let keys: Vec<(u64, AccountOwner)> = self.state.guild_members.indices().await.unwrap_or_default();
+        let mut members = Vec::new();
+        for key in keys.into_iter().filter(|(guild_id, _)| *guild_id == self.guild.id) {
+            if let Some(role) = self.state.guild_members.get(&key).await.unwrap() {
+                members.push((key.1, role));
+            }
+        }
+        members
+    }
+}
+
+#[Object(name = "Guild")]
+impl GuildObject {
+    async fn id(&self) -> u64 {
+        self.guild.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.guild.name
+    }
+
+    async fn tag(&self) -> &str {
+        &self.guild.tag
+    }
+
+    async fn level(&self) -> u32 {
+        self.guild.level
+    }
+
+    async fn experience(&self) -> u64 {
+        self.guild.experience
+    }
+
+    async fn treasury(&self) -> u64 {
+        self.guild.treasury
+    }
+
+    async fn member_count(&self) -> u32 {
+        self.guild.member_count
+    }
+
+    async fn created_at(&self) -> u64 {
+        self.guild.created_at
+    }
+
+    async fn members(&self) -> Vec<GuildMember> {
+        self.load_members()
+            .await
+            .into_iter()
+            .map(|(owner, role)| GuildMember {
+                player: PlayerObject::new(&self.state, owner),
+                role,
+            })
+            .collect()
+    }
+
+    async fn avg_elo(&self) -> u32 {
+        let members = self.load_members().await;
+        if members.is_empty() {
+            return 0;
+        }
+        let mut total: u64 = 0;
+        for (owner, _) in &members {
+            total += u64::from(self.state.player_elo.get(owner).await.unwrap().unwrap_or(1500));
+        }
+        (total / members.len() as u64) as u32
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct GuildMember {
+    pub player: PlayerObject,
+    pub role: GuildRole,
+}
+
+// ===================== EVENTS =====================
+
+#[derive(SimpleObject)]
+pub struct EventPage {
+    pub events: Vec<StreamEvent>,
+    pub next_index: u32, // `since` for the next page; the same index again if nothing is new
+}
+
+#[derive(SimpleObject)]
+pub struct StreamEvent {
+    pub index: u32, // position in the stream
+    pub event: GameEvent,
+}
+
+impl EventPage {
+    async fn read(log: &EventLogView, since: Option<u32>, limit: Option<u32>) -> Self {
+        let since = since.unwrap_or(0);
+        let limit = limit.unwrap_or(50).clamp(1, 100);
+        let end = since.saturating_add(limit).min(*log.count.get()).max(since);
+        let mut events = Vec::new();
+        for index in since..end {
+            if let Some(event) = log.events.get(&index).await.unwrap() {
+                events.push(StreamEvent { index, event });
+            }
+        }
+        EventPage {
+            events,
+            next_index: end,
+        }
+    }
+}
+
+// ===================== REPLAYS =====================
+
+#[derive(SimpleObject)]
+pub struct ReplayPage {
+    pub replays: Vec<ReplayObject>,
+    pub next_cursor: Option<u64>, // null on the last page
+}
+
+pub struct ReplayObject {
+    state: Arc<InstantTacToeState>,
+    id: u64,
+    replay: GameReplay,
+}
+
+impl ReplayObject {
+    async fn load(state: &Arc<InstantTacToeState>, replay_id: u64) -> Option<Self> {
+        Some(ReplayObject {
+            state: state.clone(),
+            id: replay_id,
+            replay: state.game_replays.get(&replay_id).await.unwrap()?,
+        })
+    }
+}
+
+#[Object(name = "Replay")]
+impl ReplayObject {
+    async fn id(&self) -> u64 {
+        self.id
+    }
+
+    async fn room_id(&self) -> u32 {
+        self.replay.room_id
+    }
+
+    async fn mode(&self) -> &GameMode {
+        &self.replay.mode
+    }
+
+    async fn players(&self) -> Vec<Option<PlayerObject>> {
+        self.replay
+            .players
+            .iter()
+            .map(|player| player.map(|owner| PlayerObject::new(&self.state, owner)))
+            .collect()
+    }
+
+    async fn winner(&self) -> Option<char> {
+        self.replay.winner
+    }
+
+    async fn stake(&self) -> Option<u64> {
+        self.replay.stake
+    }
+
+    async fn started_at(&self) -> u64 {
+        self.replay.started_at
+    }
+
+    async fn duration(&self) -> u64 {
+        self.replay.duration
+    }
+
+    async fn timestamp(&self) -> u64 {
+        self.replay.timestamp
+    }
+
+    async fn moves(&self) -> Vec<u32> {
+        self.replay.moves.clone()
+    }
+
+    async fn move_count(&self) -> u32 {
+        self.replay.moves.len() as u32
+    }
+
+    /// Whether `player` pinned the replay, exempting it from retention.
+    async fn pinned(&self, player: AccountOwner) -> bool {
+        self.replay.is_pinned_by(player)
+    }
+
+    /// Whether every move replays cleanly; power-ups are not in the move list,
+    /// so games that used them may not.
+    async fn complete(&self) -> bool {
+        GameState::from_moves(&self.replay.mode, &self.replay.moves).is_ok()
+    }
+
+    /// The game ply by ply, with the board after each move and, on 3x3
+    /// boards, the solver's verdict on the move.
+    async fn plies(&self) -> Vec<Ply> {
+        let mut game = GameState::new(&self.replay.mode);
+        let mut solver = Solver::new();
+        let mut plies = Vec::new();
+
+        for (idx, position) in self.replay.moves.iter().enumerate() {
+            let mover = game.current_player;
+            let evaluation = if game.is_ultimate() {
+                None
+            } else {
+                let evals = solver.evaluate_moves(&game.display_board(), game.to_move());
+                let played = evals.iter().find(|eval| eval.position == *position).copied();
+                MoveLabel::classify(&evals, *position).map(|label| MoveEvaluation {
+                    label,
+                    result: played.map(|eval| eval.solution.value),
+                    plies: played.map(|eval| eval.solution.plies),
+                    best_moves: solver.best_moves(&game.display_board(), game.to_move()),
+                })
+            };
+
+            if game.apply_move(*position).is_err() {
+                break;
+            }
+
+            plies.push(Ply {
+                ply: idx as u32 + 1,
+                position: *position,
+                player: rules::player_char(mover),
+                account: self.replay.players[mover as usize],
+                timestamp: self.replay.move_times.get(idx).copied(),
+                board: game.display_board(),
+                ultimate_board: game.ultimate_board().cloned(),
+                evaluation,
+            });
+        }
+        plies
+    }
+
+    /// The game in text notation, see `abi::notation`.
+    async fn notation(&self) -> String {
+        let mut players = Vec::new();
+        let mut tags = vec![("Room".to_string(), self.replay.room_id.to_string())];
+        for (player, tag) in self.replay.players.iter().zip(["XAccount", "OAccount"]) {
+            match player {
+                Some(owner) => {
+                    let nickname = self.state.nicknames.get(owner).await.unwrap();
+                    players.push(nickname.unwrap_or_else(|| owner.to_string()));
+                    tags.push((tag.to_string(), owner.to_string()));
+                }
+                None => players.push("?".to_string()),
+            }
+        }
+
+        GameRecord {
+            mode: self.replay.mode.clone(),
+            players: [players[0].clone(), players[1].clone()],
+            result: self.replay.winner,
+            started_at: Some(self.replay.started_at),
+            ended_at: Some(self.replay.timestamp),
+            moves: self.replay.moves.clone(),
+            tags,
+        }
+        .to_string()
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Ply {
+    pub ply: u32,
+    pub position: u32,
+    pub player: char,
+    pub account: Option<AccountOwner>,
+    pub timestamp: Option<u64>,
+    pub board: [Option<char>; 9],
+    pub ultimate_board: Option<UltimateBoard>,
+    pub evaluation: Option<MoveEvaluation>, // 3x3 boards only
+}
+
+#[derive(SimpleObject)]
+pub struct MoveEvaluation {
+    pub label: MoveLabel,
+    pub result: Option<solver::Value>,
+    pub plies: Option<u32>,
+    pub best_moves: Vec<u32>,
+}
+
+// ===================== ANALYSIS =====================
+
+#[derive(SimpleObject)]
+pub struct Analysis {
+    pub board: [Option<char>; 9],
+    pub to_move: char,
+    pub outcome: Option<char>, // set if the game on this board is already over
+    pub result: solver::Value, // for the side to move, with best play
+    pub plies: u32,            // until that result
+    pub moves: Vec<MoveAnalysis>,
+}
+
+#[derive(SimpleObject)]
+pub struct MoveAnalysis {
+    pub position: u32,
+    pub result: solver::Value, // for the player making the move
+    pub plies: u32,
+    pub is_best: bool,
+}
+
+/// Solves a 3x3 board and rates every legal move for the side to move.
+fn analyze(board: [Option<char>; 9], to_move: Option<char>) -> Result<Analysis, GameError> {
+    if board.iter().flatten().any(|c| *c != 'X' && *c != 'O') {
+        return Err(GameError::InvalidBoard);
+    }
+    let to_move = match to_move {
+        Some(c @ ('X' | 'O')) => c,
+        Some(_) => return Err(GameError::InvalidSideToMove),
+        None => {
+            let x_count = board.iter().filter(|c| **c == Some('X')).count();
+            let o_count = board.iter().filter(|c| **c == Some('O')).count();
+            if x_count > o_count { 'O' } else { 'X' }
+        }
+    };
+
+    let mut solver = Solver::new();
+    let position = solver.solve(&board, to_move);
+    let evals = solver.evaluate_moves(&board, to_move);
+    let best = evals.iter().map(|eval| eval.solution.score()).max();
+    let moves = evals
+        .iter()
+        .map(|eval| MoveAnalysis {
+            position: eval.position,
+            result: eval.solution.value,
+            plies: eval.solution.plies,
+            is_best: Some(eval.solution.score()) == best,
+        })
+        .collect();
+
+    Ok(Analysis {
+        board,
+        to_move,
+        outcome: rules::board_outcome(&board).map(|o| o.as_char()),
+        result: position.value,
+        plies: position.plies,
+        moves,
+    })
+}
@@ -1,5 +1,5 @@
-#![cfg_attr(target_arch = "wasm32", no_main)]
-
-include!("lib.rs");
-
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+include!("lib.rs");
+
 linera_sdk::service!(InstantTacToeService);
\ No newline at end of file
@@ -1,120 +1,420 @@
-use abi::{ChatMessage, GameMode, MatchResult, PowerUpType, TournamentFormat, TournamentStatus};
-use linera_sdk::{
-    linera_base_types::AccountOwner,
-    views::{MapView, RegisterView, RootView, ViewStorageContext},
-};
-use serde::{Deserialize, Serialize};
-
-#[derive(RootView)]
-#[view(context = ViewStorageContext)]
-pub struct InstantTacToeState {
-    // --- CORE GAME STATE ---
-    pub next_room_id: RegisterView<u32>,
-    pub next_tournament_id: RegisterView<u64>,
-    pub next_guild_id: RegisterView<u64>,
-
-    // Room Management
-    pub room_names: MapView<u32, String>,
-    pub room_creators: MapView<u32, AccountOwner>,
-    pub room_passwords: MapView<u32, Option<String>>,
-    pub room_is_full: MapView<u32, bool>,
-    pub room_modes: MapView<u32, GameMode>,
-    pub room_stakes: MapView<u32, Option<u64>>,
-
-    // Game State
-    pub game_boards: MapView<u32, [Option<char>; 9]>,
-    pub game_current_players: MapView<u32, u8>,
-    pub game_players: MapView<u32, [Option<AccountOwner>; 2]>,
-    pub game_winners: MapView<u32, Option<char>>,
-    pub game_move_times: MapView<u32, u64>,               // last move timestamp
-    pub game_moves_history: MapView<(u32, u32), u32>,     // (room_id, move_number) -> position
-
-    // --- TOURNAMENT SYSTEM ---
-    pub tournament_names: MapView<u64, String>,
-    pub tournament_formats: MapView<u64, TournamentFormat>,
-    pub tournament_status: MapView<u64, TournamentStatus>,
-    pub tournament_entry_fees: MapView<u64, Option<u64>>,
-    pub tournament_players: MapView<u64, Vec<AccountOwner>>,
-    pub tournament_brackets: MapView<u64, Vec<BracketMatch>>,
-    pub tournament_winners: MapView<u64, Vec<AccountOwner>>, // 1st, 2nd, 3rd
-    pub tournament_prize_pools: MapView<u64, u64>,
-
-    // --- ECONOMY & STAKING ---
-    pub player_balances: MapView<AccountOwner, u64>,
-    pub player_elo: MapView<AccountOwner, u32>,
-    pub player_stats: MapView<AccountOwner, (u32, u32, u32)>, // wins, losses, draws
-    pub player_streaks: MapView<AccountOwner, i32>,
-    pub player_achievements: MapView<(AccountOwner, u32), bool>,
-
-    // Staking Pools
-    pub staked_games: MapView<u32, StakedGame>,
-    pub escrow_accounts: MapView<AccountOwner, u64>,
-
-    // --- SOCIAL FEATURES ---
-    pub guilds: MapView<u64, Guild>,
-    pub guild_members: MapView<(u64, AccountOwner), GuildRole>,
-    pub player_guilds: MapView<AccountOwner, u64>,
-
-    // Chat System
-    pub chat_messages: MapView<(u32, u64), ChatMessage>,
-    pub chat_counts: MapView<u32, u64>,
-
-    // --- ADVANCED FEATURES ---
-    pub nicknames: MapView<AccountOwner, String>,
-    pub player_powerups: MapView<(AccountOwner, PowerUpType), u32>,
-    pub game_replays: MapView<u64, GameReplay>,
-
-    // NOTE: leaderboard key with GameMode can be risky long-term; keep if you want,
-    // but if it causes issues later, change to MapView<(u8, u32), AccountOwner>.
-    pub leaderboard: MapView<(GameMode, u32), AccountOwner>,
-}
-
-// ===================== SUPPORTING TYPES =====================
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BracketMatch {
-    pub match_id: u64,
-    pub player1: Option<AccountOwner>,
-    pub player2: Option<AccountOwner>,
-    pub result: Option<MatchResult>,
-    pub next_match: Option<u64>,
-    pub round: u32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StakedGame {
-    pub room_id: u32,
-    pub total_pot: u64,
-    pub players_stake: [u64; 2],
-    pub claimed: [bool; 2],
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Guild {
-    pub id: u64,
-    pub name: String,
-    pub tag: String,
-    pub level: u32,
-    pub experience: u64,
-    pub treasury: u64,
-    pub member_count: u32,
-    pub created_at: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GuildRole {
-    Leader,
-    Officer,
-    Member,
-    Recruit,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GameReplay {
-    pub room_id: u32,
-    pub moves: Vec<u32>,
-    pub players: [Option<AccountOwner>; 2],
-    pub winner: Option<char>,
-    pub duration: u64,
-    pub timestamp: u64,
-}
+use abi::{
+    error::GameError,
+    events::GameEvent,
+    rules::{ChessClock, GameState},
+    ChatMessage, Difficulty, GameConfig, GameMode, MatchResult, PowerUpType, RoomStatus,
+    TournamentFormat, TournamentStatus,
+};
+use linera_sdk::{
+    linera_base_types::AccountOwner,
+    views::{CollectionView, MapView, RegisterView, RootView, View, ViewStorageContext},
+};
+use async_graphql::{Enum, SimpleObject};
+use serde::{Deserialize, Serialize};
+
+/// Layout version of the stored state; see `migrate_storage` in the contract.
+pub const STORAGE_VERSION: u32 = 1;
+
+/// Views are stored under their position in this struct. The fields up to
+/// `leaderboard` keep the first release's order and types, so an upgraded
+/// application still finds its data there; new fields only go at the end.
+#[derive(RootView)]
+#[view(context = ViewStorageContext)]
+pub struct InstantTacToeState {
+    // --- CORE GAME STATE ---
+    pub next_room_id: RegisterView<u32>,
+    pub next_tournament_id: RegisterView<u64>,
+    pub next_guild_id: RegisterView<u64>,
+
+    // First-release rooms, moved into `rooms` by the migration
+    pub room_names: MapView<u32, String>,
+    pub room_creators: MapView<u32, AccountOwner>,
+    pub room_passwords: MapView<u32, Option<String>>,
+    pub room_is_full: MapView<u32, bool>,
+    pub room_modes: MapView<u32, GameMode>,
+    pub room_stakes: MapView<u32, Option<u64>>,
+    pub game_boards: MapView<u32, [Option<char>; 9]>,
+    pub game_current_players: MapView<u32, u8>,
+    pub game_players: MapView<u32, [Option<AccountOwner>; 2]>,
+    pub game_winners: MapView<u32, Option<char>>,
+    pub game_move_times: MapView<u32, u64>,           // last move timestamp
+    pub game_moves_history: MapView<(u32, u32), u32>, // (room_id, move_number) -> position, moved into `room_moves`
+
+    // --- TOURNAMENT SYSTEM ---
+    pub tournament_names: MapView<u64, String>,
+    pub tournament_formats: MapView<u64, TournamentFormat>,
+    pub tournament_status: MapView<u64, TournamentStatus>,
+    pub tournament_entry_fees: MapView<u64, Option<u64>>,
+    pub tournament_players: MapView<u64, Vec<AccountOwner>>,
+    pub tournament_brackets: MapView<u64, Vec<BracketMatch>>,
+    pub tournament_winners: MapView<u64, Vec<AccountOwner>>, // 1st, 2nd, 3rd
+    pub tournament_prize_pools: MapView<u64, u64>,
+
+    // --- ECONOMY & STAKING ---
+    pub player_balances: MapView<AccountOwner, u64>,
+    pub player_elo: MapView<AccountOwner, u32>,
+    pub player_stats: MapView<AccountOwner, (u32, u32, u32)>, // wins, losses, draws
+    pub player_streaks: MapView<AccountOwner, i32>,
+    pub player_achievements: MapView<(AccountOwner, u32), bool>,
+
+    // Staking Pools
+    pub staked_games: MapView<u32, StakedGame>,
+    pub escrow_accounts: MapView<AccountOwner, u64>, // first release, replaced by `room_escrow`
+
+    // --- SOCIAL FEATURES ---
+    pub guilds: MapView<u64, Guild>,
+    pub guild_members: MapView<(u64, AccountOwner), GuildRole>,
+    pub player_guilds: MapView<AccountOwner, u64>,
+
+    // First-release chat, moved into `rooms` by the migration
+    pub chat_messages: MapView<(u32, u64), ChatMessage>,
+    pub chat_counts: MapView<u32, u64>,
+
+    // --- ADVANCED FEATURES ---
+    pub nicknames: MapView<AccountOwner, String>,
+    pub player_powerups: MapView<(AccountOwner, PowerUpType), u32>,
+    pub legacy_replays: MapView<u64, LegacyReplay>, // first release, replaced by `game_replays`
+
+    // NOTE: leaderboard key with GameMode can be risky long-term; keep if you want,
+    // but if it causes issues later, change to MapView<(u8, u32), AccountOwner>.
+    pub leaderboard: MapView<(GameMode, u32), AccountOwner>,
+
+    // --- ADDED AFTER THE FIRST RELEASE ---
+    pub storage_version: RegisterView<u32>,
+    pub config: RegisterView<GameConfig>,
+    pub next_replay_id: RegisterView<u64>,
+
+    // Rooms
+    pub rooms: CollectionView<u32, RoomView>,
+    pub room_moves: CollectionView<u32, RoomMovesView>,  // move history of every game played in a room
+    pub lobby_events: EventLogView,
+
+    // Tournaments
+    pub tournament_prize_distributions: MapView<u64, Vec<u32>>, // percent of the pool per place
+    pub tournament_claims: MapView<(u64, AccountOwner), u64>,   // prizes already paid out
+
+    // Players and stakes
+    pub practice_stats: MapView<AccountOwner, (u32, u32, u32)>, // same as player_stats, for bot games
+    pub ledgers: CollectionView<AccountOwner, LedgerView>,      // every balance change, per player
+    pub room_escrow: CollectionView<AccountOwner, MapView<u32, u64>>, // owner -> room_id -> stake held until settlement
+
+    // Replays
+    pub game_replays: MapView<u64, GameReplay>,
+    pub player_replay_index: MapView<(AccountOwner, u64), u64>, // (player, seq) -> replay_id
+    pub player_replay_heads: MapView<AccountOwner, ReplayIndexHead>,
+}
+
+/// One room. The record is read and written as a whole; the chat grows
+/// without rewriting it.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct RoomView {
+    pub record: RegisterView<Option<Room>>,
+    pub chat: MapView<u64, ChatMessage>,
+    pub chat_count: RegisterView<u64>,
+    pub events: EventLogView,
+}
+
+/// Moves played in one room, numbered across all of its games.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct RoomMovesView {
+    pub count: RegisterView<u32>,
+    pub positions: MapView<u32, u32>,  // move_number -> position
+    pub timestamps: MapView<u32, u64>, // move_number -> when it was played
+}
+
+/// Copy of the events emitted on one stream, numbered like the stream.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct EventLogView {
+    pub count: RegisterView<u32>,
+    pub events: MapView<u32, GameEvent>,
+}
+
+impl EventLogView {
+    pub fn push(&mut self, event: GameEvent) {
+        let index = *self.count.get();
+        let _ = self.events.insert(&index, event);
+        self.count.set(index + 1);
+    }
+}
+
+/// A player's balance history. Entries are only ever appended, so replaying
+/// them from the start gives the current balance.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct LedgerView {
+    pub count: RegisterView<u64>,
+    pub entries: MapView<u64, Transaction>, // seq -> entry, oldest first
+}
+
+// ===================== SUPPORTING TYPES =====================
+
+/// Metadata of a room and the state of the game currently played in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Room {
+    pub name: String,
+    pub creator: AccountOwner,
+    pub password: Option<String>,
+    pub status: RoomStatus,
+    pub mode: GameMode,
+    pub stake: Option<u64>,
+    pub bot: Option<Difficulty>, // practice rooms against the built-in bot
+    pub players: [Option<AccountOwner>; 2],
+    pub game: GameState, // board + side to move
+    pub winner: Option<char>,
+    pub clock: Option<ChessClock>, // Speed rooms only
+    pub started_at: u64,           // start of the current game
+    pub last_move_at: u64,
+    pub first_move: u32, // first move_number of the current game
+    pub series: Option<MatchSeries>, // best-of-N rooms only
+    pub rematch_offer: Option<AccountOwner>,
+    pub draw_offer: Option<AccountOwner>,
+    pub takeback_request: Option<AccountOwner>,
+    pub last_replay: Option<u64>,
+}
+
+impl Room {
+    /// A room waiting for an opponent, with the creator in the first seat.
+    pub fn new(
+        name: String,
+        creator: AccountOwner,
+        password: Option<String>,
+        mode: GameMode,
+        stake: Option<u64>,
+        now: u64,
+    ) -> Self {
+        Room {
+            name,
+            creator,
+            password,
+            status: RoomStatus::WaitingForOpponent,
+            game: GameState::new(&mode),
+            mode,
+            stake,
+            bot: None,
+            players: [Some(creator), None],
+            winner: None,
+            clock: None,
+            started_at: now,
+            last_move_at: now,
+            first_move: 0,
+            series: None,
+            rematch_offer: None,
+            draw_offer: None,
+            takeback_request: None,
+            last_replay: None,
+        }
+    }
+
+    /// Fails unless the room is in one of the `allowed` states.
+    pub fn require_status(&self, allowed: &[RoomStatus]) -> Result<(), GameError> {
+        if allowed.contains(&self.status) {
+            Ok(())
+        } else {
+            Err(GameError::InvalidRoomStatus { status: self.status })
+        }
+    }
+
+    /// The only place a room's status changes; rejects transitions the lifecycle doesn't allow.
+    pub fn transition(&mut self, next: RoomStatus) -> Result<(), GameError> {
+        if !self.status.can_transition_to(next) {
+            return Err(GameError::InvalidRoomStatus { status: self.status });
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    pub fn seat_of(&self, owner: AccountOwner) -> Option<usize> {
+        self.players.iter().position(|p| *p == Some(owner))
+    }
+
+    pub fn has_player(&self, owner: AccountOwner) -> bool {
+        self.seat_of(owner).is_some()
+    }
+
+    /// Sets up a fresh game between the seated players. `first_move` is the
+    /// number the new game's first move will get in the room's history.
+    pub fn start_next_game(&mut self, now: u64, first_move: u32) {
+        self.game = GameState::new(&self.mode);
+        if let Some(clock) = self.clock.as_mut() {
+            clock.reset();
+            clock.start(0, now);
+        }
+        self.winner = None;
+        self.started_at = now;
+        self.last_move_at = now;
+        self.first_move = first_move;
+        self.draw_offer = None;
+        self.takeback_request = None;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct BracketMatch {
+    pub match_id: u64,
+    pub player1: Option<AccountOwner>,
+    pub player2: Option<AccountOwner>,
+    pub result: Option<MatchResult>,
+    pub next_match: Option<u64>,
+    pub round: u32,
+}
+
+/// Score of a best-of-N series played in one room. `players` keeps the seats
+/// of the first game, even though colors swap from game to game.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct MatchSeries {
+    pub best_of: u32,
+    pub players: [Option<AccountOwner>; 2],
+    pub wins: [u32; 2],
+    pub draws: u32,
+}
+
+impl MatchSeries {
+    pub fn new(best_of: u32, players: [Option<AccountOwner>; 2]) -> Self {
+        MatchSeries {
+            best_of,
+            players,
+            wins: [0, 0],
+            draws: 0,
+        }
+    }
+
+    pub fn games_played(&self) -> u32 {
+        self.wins[0] + self.wins[1] + self.draws
+    }
+
+    /// Records a finished game; `None` is a draw.
+    pub fn record(&mut self, winner: Option<AccountOwner>) {
+        match winner.and_then(|w| self.players.iter().position(|p| *p == Some(w))) {
+            Some(idx) => self.wins[idx] += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    /// Decided once a player can no longer be caught, or all games are played.
+    pub fn is_decided(&self) -> bool {
+        self.wins.iter().any(|w| *w > self.best_of / 2) || self.games_played() >= self.best_of
+    }
+
+    /// Series result in the usual 'X' / 'O' / 'T' form, relative to `players`.
+    pub fn result(&self) -> char {
+        match self.wins[0].cmp(&self.wins[1]) {
+            std::cmp::Ordering::Greater => 'X',
+            std::cmp::Ordering::Less => 'O',
+            std::cmp::Ordering::Equal => 'T',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakedGame {
+    pub room_id: u32,
+    pub total_pot: u64,
+    pub players_stake: [u64; 2],
+    pub claimed: [bool; 2],
+}
+
+/// One change to a player's balance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub kind: TransactionKind,
+    pub amount: u64,
+    pub balance: u64, // balance right after the change
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionKind {
+    Deposit,
+    Withdraw,
+    StakeLocked { room_id: u32 },
+    StakeWon { room_id: u32 },      // the whole pot, own stake included
+    StakeRefunded { room_id: u32 }, // draws and cancelled rooms
+    EntryFee { tournament_id: u64 },
+    PrizePaid { tournament_id: u64 },
+}
+
+impl TransactionKind {
+    /// Whether the entry added to the balance rather than taking from it.
+    pub fn is_credit(self) -> bool {
+        !matches!(
+            self,
+            TransactionKind::Withdraw
+                | TransactionKind::StakeLocked { .. }
+                | TransactionKind::EntryFee { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Guild {
+    pub id: u64,
+    pub name: String,
+    pub tag: String,
+    pub level: u32,
+    pub experience: u64,
+    pub treasury: u64,
+    pub member_count: u32,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum GuildRole {
+    Leader,
+    Officer,
+    Member,
+    Recruit,
+}
+
+/// A finished game. Records carry no version tag, so a new field makes the
+/// stored ones undecodable: a changed layout needs a new map, the way the
+/// first release's records moved aside to `legacy_replays`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameReplay {
+    pub room_id: u32,
+    pub mode: GameMode,
+    pub moves: Vec<u32>,
+    pub move_times: Vec<u64>, // when each move was played
+    pub players: [Option<AccountOwner>; 2],
+    pub winner: Option<char>,
+    pub stake: Option<u64>,
+    pub started_at: u64,
+    pub duration: u64,
+    pub timestamp: u64,
+    pub pinned: [bool; 2], // per seat, pinned replays are exempt from retention
+    pub kept: [bool; 2],   // per seat, still in that player's replay index
+}
+
+/// A replay as the first release stored it, kept only so its slot in the
+/// root view keeps its type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyReplay {
+    pub room_id: u32,
+    pub moves: Vec<u32>,
+    pub players: [Option<AccountOwner>; 2],
+    pub winner: Option<char>,
+    pub duration: u64,
+    pub timestamp: u64,
+}
+
+/// Bookkeeping for one player's entries in `player_replay_index`. Entries are
+/// numbered in the order the games ended; every entry below `oldest_unpinned`
+/// is either pinned or already pruned.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayIndexHead {
+    pub next_seq: u64,
+    pub oldest_unpinned: u64,
+    pub unpinned: u32,
+}
+
+impl GameReplay {
+    pub fn is_pinned_by(&self, owner: AccountOwner) -> bool {
+        self.players
+            .iter()
+            .zip(self.pinned)
+            .any(|(player, pinned)| pinned && *player == Some(owner))
+    }
+}